@@ -1,7 +1,35 @@
 #[cfg(feature = "bench")]
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 #[cfg(feature = "bench")]
-use snowflake::SnowflakeGenerator;
+use snowflake::{Snowflake, SnowflakeGenerator, AIRDASH_EPOCH};
+
+/// The generator before the increment was reset per millisecond, without the
+/// millisecond check in `generate()`.
+#[cfg(feature = "bench")]
+struct MonotonicGenerator {
+  worker: u8,
+  process: u8,
+  increment: u16,
+}
+
+#[cfg(feature = "bench")]
+impl MonotonicGenerator {
+  fn new(worker: u8, process: u8) -> Self {
+    Self {
+      worker,
+      process,
+      increment: 0,
+    }
+  }
+
+  fn generate(&mut self) -> Snowflake {
+    let snowflake = Snowflake::new_with_epoch(self.worker, self.process, self.increment, AIRDASH_EPOCH);
+
+    self.increment = self.increment.wrapping_add(1);
+
+    snowflake
+  }
+}
 
 #[cfg(feature = "bench")]
 pub fn criterion_benchmark(c: &mut Criterion) {
@@ -20,6 +48,22 @@ pub fn criterion_benchmark(c: &mut Criterion) {
       black_box(generator.take(50_000).collect::<Vec<_>>())
     })
   });
+
+  c.bench_function("monotonic_generator", |b| {
+    b.iter(|| {
+      let mut generator = MonotonicGenerator::new(23, 12);
+
+      black_box(generator.generate())
+    })
+  });
+
+  c.bench_function("monotonic_generator_group", |b| {
+    b.iter(|| {
+      let mut generator = MonotonicGenerator::new(23, 12);
+
+      black_box((0..50_000).map(|_| generator.generate()).collect::<Vec<_>>())
+    })
+  });
 }
 
 #[cfg(feature = "bench")]
@@ -0,0 +1,132 @@
+use crate::Snowflake;
+
+/// Estimates how many snowflakes per second were generated, given that
+/// `count` snowflakes were generated from `first` to `last`. Returns
+/// [`f64::INFINITY`] if both were created in the same millisecond.
+pub fn approximate_rate(first: &Snowflake, last: &Snowflake, count: u64) -> f64 {
+  let span_ms = last.offset_timestamp().abs_diff(first.offset_timestamp());
+
+  if span_ms == 0 {
+    return f64::INFINITY;
+  }
+
+  count as f64 * 1000.0 / span_ms as f64
+}
+
+/// Finds consecutive pairs in `ids`, which is expected to be sorted, that
+/// share their millisecond and node but skip over at least one increment.
+pub fn find_sequence_gaps(ids: &[Snowflake]) -> Vec<(Snowflake, Snowflake)> {
+  ids
+    .windows(2)
+    .map(|pair| (pair[0], pair[1]))
+    .filter(|(previous, next)| {
+      previous.timestamp() == next.timestamp()
+        && previous.is_same_node(next)
+        && next.increment() > previous.increment() + 1
+    })
+    .collect()
+}
+
+/// Yields the snowflakes of `iter` with a greater value than `cursor`, e.g.
+/// to resume a sync after the last snowflake seen.
+pub fn newer_than(iter: impl Iterator<Item = Snowflake>, cursor: Snowflake) -> impl Iterator<Item = Snowflake> {
+  iter.filter(move |id| id.value() > cursor.value())
+}
+
+/// The fields of many snowflakes, decoded into one column per field.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DecodedColumns {
+  /// Unix timestamps in milliseconds.
+  pub timestamps: Vec<u64>,
+  pub workers: Vec<u8>,
+  pub processes: Vec<u8>,
+  pub increments: Vec<u16>,
+}
+
+/// Decodes `ids` into a [`DecodedColumns`], where index `i` of every column
+/// belongs to `ids[i]`. Each snowflake is decoded with its own epoch.
+pub fn decode_columns(ids: &[Snowflake]) -> DecodedColumns {
+  let mut columns = DecodedColumns {
+    timestamps: Vec::with_capacity(ids.len()),
+    workers: Vec::with_capacity(ids.len()),
+    processes: Vec::with_capacity(ids.len()),
+    increments: Vec::with_capacity(ids.len()),
+  };
+
+  for id in ids {
+    columns.timestamps.push(id.offset_timestamp());
+    columns.workers.push(id.worker());
+    columns.processes.push(id.process());
+    columns.increments.push(id.increment());
+  }
+
+  columns
+}
+
+#[cfg(test)]
+mod tests {
+  use time::macros::datetime;
+
+  use super::*;
+
+  #[test]
+  fn test_approximate_rate() {
+    let first = Snowflake::new_with_timestamp(8, 26, 0, datetime!(2022-07-08 09:10:11 UTC));
+    let last = Snowflake::new_with_timestamp(8, 26, 0, datetime!(2022-07-08 09:10:13.500 UTC));
+
+    assert_eq!(approximate_rate(&first, &last, 5_000), 2_000.0);
+    assert_eq!(approximate_rate(&last, &first, 5_000), 2_000.0);
+    assert_eq!(approximate_rate(&first, &first.with_increment(1), 2), f64::INFINITY);
+  }
+
+  #[test]
+  fn test_find_sequence_gaps() {
+    let first = Snowflake::new_with_timestamp(8, 26, 0, datetime!(2022-07-08 09:10:11 UTC));
+    let next_millisecond = Snowflake::new_with_timestamp(8, 26, 5, datetime!(2022-07-08 09:10:11.001 UTC));
+
+    let ids = [
+      first,
+      first.with_increment(1),
+      first.with_increment(4),
+      first.with_increment(5),
+      first.with_increment(7),
+      next_millisecond,
+      next_millisecond.with_process(27).with_increment(9),
+    ];
+
+    assert_eq!(
+      find_sequence_gaps(&ids),
+      vec![
+        (first.with_increment(1), first.with_increment(4)),
+        (first.with_increment(5), first.with_increment(7)),
+      ]
+    );
+    assert!(find_sequence_gaps(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_decode_columns() {
+    let first = Snowflake::new_with_timestamp(8, 26, 0, datetime!(2022-07-08 09:10:11 UTC));
+    let ids = [first, first.with_increment(1), Snowflake::from(175928847299117063u64)];
+
+    let columns = decode_columns(&ids);
+
+    assert_eq!(columns.timestamps, ids.map(|id| id.offset_timestamp()));
+    assert_eq!(columns.workers, ids.map(|id| id.worker()));
+    assert_eq!(columns.processes, ids.map(|id| id.process()));
+    assert_eq!(columns.increments, ids.map(|id| id.increment()));
+    assert_eq!(decode_columns(&[]), DecodedColumns::default());
+  }
+
+  #[test]
+  fn test_newer_than() {
+    let first = Snowflake::new_with_timestamp(8, 26, 0, datetime!(2022-07-08 09:10:11 UTC));
+    let ids: Vec<_> = (0..6).map(|increment| first.with_increment(increment)).collect();
+
+    assert_eq!(
+      newer_than(ids.clone().into_iter(), ids[2]).collect::<Vec<_>>(),
+      ids[3..]
+    );
+    assert_eq!(newer_than(ids.clone().into_iter(), ids[5]).count(), 0);
+  }
+}
@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use tokio::task::JoinHandle;
+
+use crate::{ClockSource, ExhaustionPolicy, Snowflake, SnowflakeError, SnowflakeGenerator, SystemClock};
+
+#[derive(Debug)]
+pub struct AsyncSnowflakeGenerator<C = SystemClock> {
+  generator: SnowflakeGenerator<C>,
+  lease: Option<Lease>,
+}
+
+#[derive(Debug)]
+struct Lease {
+  valid: Arc<AtomicBool>,
+  renewal: JoinHandle<()>,
+}
+
+impl<C: ClockSource> AsyncSnowflakeGenerator<C> {
+  /// Replaces the [`ExhaustionPolicy`] of `generator`: once a millisecond is
+  /// exhausted, [`generate`](Self::generate) sleeps on the tokio timer
+  /// instead of blocking the executor.
+  pub fn new(generator: SnowflakeGenerator<C>) -> Self {
+    Self {
+      generator: generator.with_exhaustion_policy(ExhaustionPolicy::Error),
+      lease: None,
+    }
+  }
+
+  /// Ties generation to a worker lease: a task on the current tokio runtime
+  /// calls `renew` every `interval`, and once it returns `false` every
+  /// following [`generate`](Self::generate) fails with
+  /// [`SnowflakeError::LeaseLost`]. The lease is assumed to be held when this
+  /// is called.
+  ///
+  /// Snowflakes generated before a renewal failed are not revoked, so a lease
+  /// lost in between renewals is only noticed after the next renewal. Pick an
+  /// interval well below the lease's expiry.
+  ///
+  /// Panics when called outside of a tokio runtime.
+  pub fn with_lease<F>(mut self, renew: F, interval: Duration) -> Self
+  where F: Fn() -> BoxFuture<'static, bool> + Send + 'static {
+    let valid = Arc::new(AtomicBool::new(true));
+
+    let renewal = tokio::spawn({
+      let valid = valid.clone();
+
+      async move {
+        loop {
+          tokio::time::sleep(interval).await;
+
+          if !renew().await {
+            valid.store(false, Ordering::Release);
+            break;
+          }
+        }
+      }
+    });
+
+    if let Some(lease) = self.lease.replace(Lease { valid, renewal }) {
+      lease.renewal.abort();
+    }
+
+    self
+  }
+
+  pub async fn generate(&mut self) -> Result<Snowflake, SnowflakeError> {
+    if let Some(lease) = &self.lease {
+      if !lease.valid.load(Ordering::Acquire) {
+        return Err(SnowflakeError::LeaseLost);
+      }
+    }
+
+    loop {
+      match self.generator.try_generate() {
+        Err(SnowflakeError::SequenceExhausted) => tokio::time::sleep(Duration::from_millis(1)).await,
+        result => return result,
+      }
+    }
+  }
+}
+
+impl<C> Drop for AsyncSnowflakeGenerator<C> {
+  fn drop(&mut self) {
+    if let Some(lease) = &self.lease {
+      lease.renewal.abort();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::AtomicUsize;
+
+  use super::*;
+  use crate::{ManualClock, AIRDASH_EPOCH, INCREMENT_MAX};
+
+  const WORKER: u8 = 8;
+  const PROCESS: u8 = 26;
+  const INTERVAL: Duration = Duration::from_millis(10);
+
+  #[tokio::test]
+  async fn test_generate_without_lease() {
+    let mut generator = AsyncSnowflakeGenerator::new(SnowflakeGenerator::new(WORKER, PROCESS));
+
+    let snowflake = generator.generate().await.unwrap();
+
+    assert_eq!(snowflake.worker(), WORKER);
+    assert_eq!(snowflake.process(), PROCESS);
+  }
+
+  #[tokio::test]
+  async fn test_exhaustion_yields_to_executor() {
+    let clock = ManualClock::new(AIRDASH_EPOCH + 1_000);
+    let mut generator = AsyncSnowflakeGenerator::new(SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock));
+
+    for _ in 0..=INCREMENT_MAX {
+      generator.generate().await.unwrap();
+    }
+
+    // on this single-threaded runtime, the clock only advances if generate
+    // doesn't block the thread
+    let (snowflake, ()) = tokio::join!(generator.generate(), async {
+      tokio::time::sleep(INTERVAL).await;
+      clock.advance(1);
+    });
+    let snowflake = snowflake.unwrap();
+
+    assert_eq!((snowflake.timestamp(), snowflake.increment()), (1_001, 0));
+  }
+
+  #[tokio::test]
+  async fn test_generate_with_renewed_lease() {
+    let renewals = Arc::new(AtomicUsize::new(0));
+
+    let mut generator = AsyncSnowflakeGenerator::new(SnowflakeGenerator::new(WORKER, PROCESS)).with_lease(
+      {
+        let renewals = renewals.clone();
+        move || {
+          renewals.fetch_add(1, Ordering::Relaxed);
+          Box::pin(async { true })
+        }
+      },
+      INTERVAL,
+    );
+
+    tokio::time::sleep(INTERVAL * 5).await;
+
+    assert!(renewals.load(Ordering::Relaxed) > 0);
+    assert!(generator.generate().await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn test_generate_with_lost_lease() {
+    let mut generator = AsyncSnowflakeGenerator::new(SnowflakeGenerator::new(WORKER, PROCESS))
+      .with_lease(|| Box::pin(async { false }), INTERVAL);
+
+    assert!(generator.generate().await.is_ok());
+
+    tokio::time::sleep(INTERVAL * 5).await;
+
+    assert!(matches!(generator.generate().await, Err(SnowflakeError::LeaseLost)));
+  }
+}
@@ -0,0 +1,51 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::Snowflake;
+
+// SAFETY: `Snowflake` is a `repr(transparent)` wrapper around a `u128`, which
+// is `Pod`; any bit pattern is a valid snowflake.
+unsafe impl Zeroable for Snowflake {}
+
+// SAFETY: see above.
+unsafe impl Pod for Snowflake {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::SnowflakeGenerator;
+
+  #[test]
+  fn test_cast_slice() {
+    let snowflakes = SnowflakeGenerator::new(8, 26).take(10).collect::<Vec<_>>();
+
+    let raw: &[u128] = bytemuck::cast_slice(&snowflakes);
+
+    assert_eq!(raw.len(), snowflakes.len());
+
+    for (raw, snowflake) in raw.iter().zip(&snowflakes) {
+      assert_eq!(*raw as u64, snowflake.value());
+      assert_eq!((*raw >> 64) as u64, snowflake.epoch());
+    }
+
+    assert_eq!(bytemuck::cast_slice::<u128, Snowflake>(raw), snowflakes);
+  }
+
+  #[test]
+  #[cfg(target_endian = "little")]
+  fn test_cast_slice_u64() {
+    let snowflakes = SnowflakeGenerator::new(8, 26).take(10).collect::<Vec<_>>();
+
+    let raw: &[u64] = bytemuck::cast_slice(&snowflakes);
+
+    let values = raw.chunks(2).map(|pair| pair[0]).collect::<Vec<_>>();
+    let epochs = raw.chunks(2).map(|pair| pair[1]).collect::<Vec<_>>();
+
+    assert_eq!(values, snowflakes.iter().map(Snowflake::value).collect::<Vec<_>>());
+    assert_eq!(epochs, snowflakes.iter().map(Snowflake::epoch).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_zeroed() {
+    assert_eq!(Snowflake::zeroed(), Snowflake(0));
+  }
+}
@@ -0,0 +1,82 @@
+//! The constructors of [`Snowflake`], but returning an error instead of
+//! truncating fields or wrapping the timestamp around, see
+//! [`Snowflake::try_new_with_timestamp_and_epoch`].
+
+use time::OffsetDateTime;
+
+use crate::{Snowflake, SnowflakeError, AIRDASH_EPOCH};
+
+pub fn new(worker: u8, process: u8, increment: u16) -> Result<Snowflake, SnowflakeError> {
+  new_with_timestamp_and_epoch(worker, process, increment, OffsetDateTime::now_utc(), AIRDASH_EPOCH)
+}
+
+pub fn new_with_timestamp(
+  worker: u8,
+  process: u8,
+  increment: u16,
+  timestamp: OffsetDateTime,
+) -> Result<Snowflake, SnowflakeError> {
+  new_with_timestamp_and_epoch(worker, process, increment, timestamp, AIRDASH_EPOCH)
+}
+
+pub fn new_with_epoch(worker: u8, process: u8, increment: u16, epoch: u64) -> Result<Snowflake, SnowflakeError> {
+  new_with_timestamp_and_epoch(worker, process, increment, OffsetDateTime::now_utc(), epoch)
+}
+
+pub fn new_with_timestamp_and_epoch(
+  worker: u8,
+  process: u8,
+  increment: u16,
+  timestamp: OffsetDateTime,
+  epoch: u64,
+) -> Result<Snowflake, SnowflakeError> {
+  Snowflake::try_new_with_timestamp_and_epoch(worker, process, increment, timestamp, epoch)
+}
+
+#[cfg(test)]
+mod tests {
+  use time::macros::datetime;
+
+  use super::*;
+  use crate::{INCREMENT_MAX, PROCESS_MAX, WORKER_MAX};
+
+  const TIMESTAMP: OffsetDateTime = datetime!(2022-07-08 09:10:11 UTC);
+
+  #[test]
+  fn test_matches_unchecked() {
+    assert_eq!(
+      new_with_timestamp(8, 26, 543, TIMESTAMP).unwrap(),
+      Snowflake::new_with_timestamp(8, 26, 543, TIMESTAMP)
+    );
+    assert_eq!(new(8, 26, 543).unwrap().epoch(), AIRDASH_EPOCH);
+    assert_eq!(new_with_epoch(8, 26, 543, 0).unwrap().epoch(), 0);
+  }
+
+  #[test]
+  fn test_out_of_range_fields() {
+    let field = |result: Result<Snowflake, SnowflakeError>| match result {
+      Err(SnowflakeError::FieldOutOfRange { field, .. }) => field,
+      result => panic!("expected a field out of range, got {result:?}"),
+    };
+
+    assert_eq!(field(new(WORKER_MAX + 1, 0, 0)), "worker");
+    assert_eq!(field(new_with_timestamp(0, PROCESS_MAX + 1, 0, TIMESTAMP)), "process");
+    assert_eq!(field(new_with_epoch(0, 0, INCREMENT_MAX + 1, 0)), "increment");
+  }
+
+  #[test]
+  fn test_timestamp_out_of_range() {
+    assert!(matches!(
+      new_with_timestamp(8, 26, 0, datetime!(2014-12-31 23:59:59.999 UTC)),
+      Err(SnowflakeError::TimestampBeforeEpoch)
+    ));
+    assert!(matches!(
+      new_with_timestamp_and_epoch(8, 26, 0, datetime!(1969-12-31 23:59 UTC), 0),
+      Err(SnowflakeError::TimestampBeforeEpoch)
+    ));
+    assert!(matches!(
+      new_with_timestamp_and_epoch(8, 26, 0, datetime!(2200-01-01 0:00 UTC), 0),
+      Err(SnowflakeError::TimestampOverflow)
+    ));
+  }
+}
@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Source of the current time used by
+/// [`SnowflakeGenerator`](crate::SnowflakeGenerator).
+pub trait ClockSource {
+  /// Current unix timestamp in milliseconds.
+  fn millis(&self) -> u64;
+}
+
+/// Wall clock of the system. With the `wasm` feature on `wasm32` targets this
+/// reads `Date.now()`, as the system time isn't available in the browser.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl ClockSource for SystemClock {
+  #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+  fn millis(&self) -> u64 { crate::millis(time::OffsetDateTime::now_utc()) }
+
+  #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+  fn millis(&self) -> u64 { js_sys::Date::now() as u64 }
+}
+
+/// Wall clock that can't go backwards, e.g. across leap seconds or NTP
+/// corrections: it reads the wall clock once and then adds the time passed
+/// according to [`Instant`]. Drifts from the wall clock the longer it runs.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicWallClock {
+  base_millis: u64,
+  base: Instant,
+}
+
+impl MonotonicWallClock {
+  pub fn new() -> Self { Self::from_clock(&SystemClock) }
+
+  /// Captures the current time of `clock` as the base.
+  pub fn from_clock(clock: &impl ClockSource) -> Self {
+    Self {
+      base_millis: clock.millis(),
+      base: Instant::now(),
+    }
+  }
+}
+
+impl Default for MonotonicWallClock {
+  fn default() -> Self { Self::new() }
+}
+
+impl ClockSource for MonotonicWallClock {
+  fn millis(&self) -> u64 { self.base_millis + self.base.elapsed().as_millis() as u64 }
+}
+
+/// Clock that only moves when told to, for tests. Pass it by reference to
+/// [`SnowflakeGenerator::with_clock`](crate::SnowflakeGenerator::with_clock)
+/// to keep advancing it while the generator uses it.
+#[derive(Debug, Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+  pub fn new(millis: u64) -> Self { Self(AtomicU64::new(millis)) }
+
+  pub fn advance(&self, ms: u64) { self.0.fetch_add(ms, Ordering::Relaxed); }
+
+  pub fn set(&self, ms: u64) { self.0.store(ms, Ordering::Relaxed); }
+}
+
+impl ClockSource for ManualClock {
+  fn millis(&self) -> u64 { self.0.load(Ordering::Relaxed) }
+}
+
+impl<C: ClockSource + ?Sized> ClockSource for &C {
+  fn millis(&self) -> u64 { (**self).millis() }
+}
+
+#[cfg(test)]
+mod tests {
+  use time::OffsetDateTime;
+
+  use super::*;
+  use crate::millis;
+
+  #[test]
+  fn test_system_clock() {
+    let before = millis(OffsetDateTime::now_utc());
+    let now = SystemClock.millis();
+
+    assert!(now >= before);
+    assert!(now - before < 1_000);
+  }
+
+  #[test]
+  fn test_manual_clock() {
+    use crate::{SnowflakeGenerator, AIRDASH_EPOCH};
+
+    let clock = ManualClock::new(AIRDASH_EPOCH + 1_000);
+    let mut generator = SnowflakeGenerator::new(8, 26).with_clock(&clock);
+
+    generator.generate();
+    let before = generator.generate();
+    clock.advance(5);
+    let after = generator.generate();
+
+    assert_eq!(before.increment(), 1);
+    assert_eq!(after.timestamp() - before.timestamp(), 5);
+    assert_eq!(after.increment(), 0);
+
+    clock.set(AIRDASH_EPOCH + 2_000);
+    assert_eq!(generator.generate().timestamp(), 2_000);
+  }
+
+  #[test]
+  fn test_monotonic_wall_clock() {
+    let wall = ManualClock::new(1_000_000);
+    let clock = MonotonicWallClock::from_clock(&wall);
+    let mut previous = clock.millis();
+
+    assert!(previous >= 1_000_000);
+
+    for _ in 0..100 {
+      wall.set(wall.millis() - 1_000);
+      let now = clock.millis();
+
+      assert!(now >= previous);
+      previous = now;
+    }
+
+    assert!(MonotonicWallClock::new().millis() >= millis(OffsetDateTime::now_utc()) - 1_000);
+  }
+
+  #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+  #[wasm_bindgen_test::wasm_bindgen_test]
+  fn test_wasm_system_clock() {
+    use crate::{SnowflakeGenerator, AIRDASH_EPOCH};
+
+    let mut generator = SnowflakeGenerator::new(0, 0);
+
+    assert!(SystemClock.millis() > AIRDASH_EPOCH);
+    assert!(generator.generate().offset_timestamp() > AIRDASH_EPOCH);
+  }
+}
@@ -0,0 +1,183 @@
+//! Alternative encodings for `#[serde(with = "...")]` fields.
+
+/// For `#[serde(with = "snowflake::serde::decoded")]`, serializes the decoded
+/// fields along with the id:
+///
+/// ```json
+/// { "id": "175928847299117063", "worker": 1, "process": 0, "increment": 7, "timestamp": 1462015105796 }
+/// ```
+///
+/// `timestamp` is in unix milliseconds. Only `id` is read when deserializing,
+/// with [`AIRDASH_EPOCH`](crate::AIRDASH_EPOCH) like
+/// [`Snowflake::from_value`](crate::Snowflake::from_value).
+pub mod decoded {
+  use serde::ser::SerializeStruct;
+  use serde::{Deserialize, Deserializer, Serializer};
+
+  use crate::Snowflake;
+
+  #[derive(Deserialize)]
+  struct Decoded {
+    id: Snowflake,
+  }
+
+  pub fn serialize<S>(snowflake: &Snowflake, serializer: S) -> Result<S::Ok, S::Error>
+  where S: Serializer {
+    let mut decoded = serializer.serialize_struct("Snowflake", 5)?;
+    decoded.serialize_field("id", snowflake)?;
+    decoded.serialize_field("worker", &snowflake.worker())?;
+    decoded.serialize_field("process", &snowflake.process())?;
+    decoded.serialize_field("increment", &snowflake.increment())?;
+    decoded.serialize_field("timestamp", &snowflake.offset_timestamp())?;
+    decoded.end()
+  }
+
+  pub fn deserialize<'de, D>(deserializer: D) -> Result<Snowflake, D::Error>
+  where D: Deserializer<'de> {
+    Decoded::deserialize(deserializer).map(|decoded| decoded.id)
+  }
+}
+
+/// For `#[serde(with = "snowflake::serde::byte_array")]`, serializes the
+/// big-endian bytes of the value, e.g. `[2, 113, 6, 90, 193, 2, 0, 7]` in
+/// JSON. Deserializing requires exactly 8 bytes and uses
+/// [`AIRDASH_EPOCH`](crate::AIRDASH_EPOCH) like
+/// [`Snowflake::from_value`](crate::Snowflake::from_value).
+pub mod byte_array {
+  use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+  use crate::Snowflake;
+
+  pub fn serialize<S>(snowflake: &Snowflake, serializer: S) -> Result<S::Ok, S::Error>
+  where S: Serializer {
+    <[u8; 8]>::from(*snowflake).serialize(serializer)
+  }
+
+  pub fn deserialize<'de, D>(deserializer: D) -> Result<Snowflake, D::Error>
+  where D: Deserializer<'de> {
+    <[u8; 8]>::deserialize(deserializer).map(Snowflake::from)
+  }
+}
+
+/// For `#[serde(with = "snowflake::codecs::cbor")]` with the `ciborium`
+/// feature, serializes the value as an integer under the CBOR tag
+/// [`CBOR_TAG`](cbor::CBOR_TAG), so consumers can tell it's a snowflake.
+/// Deserializing accepts the tagged and the bare integer.
+#[cfg(feature = "ciborium")]
+pub mod cbor {
+  use ciborium::tag::{Accepted, Required};
+  use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+  use crate::Snowflake;
+
+  /// `SNOW` in ASCII. Not registered with IANA, it's in the first come first
+  /// served range.
+  pub const CBOR_TAG: u64 = 0x534E4F57;
+
+  pub fn serialize<S>(snowflake: &Snowflake, serializer: S) -> Result<S::Ok, S::Error>
+  where S: Serializer {
+    Required::<u64, CBOR_TAG>(snowflake.value()).serialize(serializer)
+  }
+
+  pub fn deserialize<'de, D>(deserializer: D) -> Result<Snowflake, D::Error>
+  where D: Deserializer<'de> {
+    Accepted::<u64, CBOR_TAG>::deserialize(deserializer).map(|value| Snowflake::from_value(value.0))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::{Deserialize, Serialize};
+  use serde_json::json;
+
+  use crate::Snowflake;
+
+  #[test]
+  fn test_decoded() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+      #[serde(with = "crate::serde::decoded")]
+      id: Snowflake,
+    }
+
+    let message = Message {
+      id: Snowflake::from_value(175928847299117063),
+    };
+
+    let json = serde_json::to_value(&message).unwrap();
+
+    assert_eq!(
+      json,
+      json!({
+        "id": {
+          "id": "175928847299117063",
+          "worker": 1,
+          "process": 0,
+          "increment": 7,
+          "timestamp": 1462015105796u64,
+        }
+      })
+    );
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message);
+    assert_eq!(
+      serde_json::from_value::<Message>(json!({ "id": { "id": 175928847299117063u64 } })).unwrap(),
+      message
+    );
+  }
+
+  #[test]
+  fn test_byte_array() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+      #[serde(with = "crate::serde::byte_array")]
+      id: Snowflake,
+    }
+
+    let message = Message {
+      id: Snowflake::from_value(175928847299117063),
+    };
+
+    let json = serde_json::to_value(&message).unwrap();
+
+    assert_eq!(json, json!({ "id": [2, 113, 6, 90, 193, 2, 0, 7] }));
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message);
+    assert!(serde_json::from_value::<Message>(json!({ "id": [2, 113, 6, 90, 193, 2, 0] })).is_err());
+    assert!(serde_json::from_value::<Message>(json!({ "id": [2, 113, 6, 90, 193, 2, 0, 7, 0] })).is_err());
+  }
+
+  #[cfg(feature = "ciborium")]
+  #[test]
+  fn test_cbor() {
+    use ciborium::Value;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+      #[serde(with = "crate::codecs::cbor")]
+      id: Snowflake,
+    }
+
+    let message = Message {
+      id: Snowflake::from_value(175928847299117063),
+    };
+
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&message, &mut bytes).unwrap();
+
+    let value: Value = ciborium::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(
+      value,
+      Value::Map(vec![(
+        Value::Text("id".into()),
+        Value::Tag(
+          super::cbor::CBOR_TAG,
+          Box::new(Value::Integer(175928847299117063u64.into()))
+        )
+      )])
+    );
+    assert_eq!(ciborium::from_reader::<Message, _>(bytes.as_slice()).unwrap(), message);
+
+    let mut bare = Vec::new();
+    ciborium::into_writer(&json!({ "id": 175928847299117063u64 }), &mut bare).unwrap();
+    assert_eq!(ciborium::from_reader::<Message, _>(bare.as_slice()).unwrap(), message);
+  }
+}
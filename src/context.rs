@@ -0,0 +1,92 @@
+use time::OffsetDateTime;
+
+use crate::Snowflake;
+
+/// Fields of a snowflake decoded by [`SnowflakeContext::decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodedSnowflake {
+  /// Unix timestamp in milliseconds.
+  pub timestamp: u64,
+  pub worker: u8,
+  pub process: u8,
+  pub increment: u16,
+}
+
+/// Epoch to decode and create snowflakes with, for values that don't come
+/// with their epoch, e.g. parsed from strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnowflakeContext {
+  pub epoch: u64,
+}
+
+impl SnowflakeContext {
+  pub fn new(epoch: u64) -> Self { Self { epoch } }
+
+  /// Decodes `id` with this context's epoch, ignoring the one it carries.
+  pub fn decode(&self, id: &Snowflake) -> DecodedSnowflake {
+    let id = id.with_epoch(self.epoch);
+
+    DecodedSnowflake {
+      timestamp: id.offset_timestamp(),
+      worker: id.worker(),
+      process: id.process(),
+      increment: id.increment(),
+    }
+  }
+
+  pub fn new_snowflake(&self, worker: u8, process: u8, increment: u16) -> Snowflake {
+    Snowflake::new_with_epoch(worker, process, increment, self.epoch)
+  }
+
+  pub fn new_snowflake_with_timestamp(
+    &self,
+    worker: u8,
+    process: u8,
+    increment: u16,
+    timestamp: OffsetDateTime,
+  ) -> Snowflake {
+    Snowflake::new_with_timestamp_and_epoch(worker, process, increment, timestamp, self.epoch)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use time::macros::datetime;
+
+  use super::*;
+  use crate::AIRDASH_EPOCH;
+
+  const EPOCH: u64 = 1_600_000_000_000;
+
+  #[test]
+  fn test_decode() {
+    let context = SnowflakeContext::new(EPOCH);
+    let id = Snowflake::from_value(175928847299117063);
+
+    assert_eq!(
+      context.decode(&id),
+      DecodedSnowflake {
+        timestamp: id.timestamp() + EPOCH,
+        worker: 1,
+        process: 0,
+        increment: 7,
+      }
+    );
+    assert_eq!(
+      SnowflakeContext::new(AIRDASH_EPOCH).decode(&id).timestamp,
+      1462015105796
+    );
+  }
+
+  #[test]
+  fn test_new_snowflake() {
+    let context = SnowflakeContext::new(EPOCH);
+    let timestamp = datetime!(2022-07-08 09:10:11 UTC);
+    let snowflake = context.new_snowflake_with_timestamp(8, 26, 543, timestamp);
+
+    assert_eq!(snowflake.epoch(), EPOCH);
+    assert_eq!(snowflake.to_datetime(), timestamp);
+    assert_eq!(context.new_snowflake(8, 26, 543).epoch(), EPOCH);
+    assert_eq!(context.decode(&snowflake).increment, 543);
+  }
+}
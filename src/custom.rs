@@ -0,0 +1,143 @@
+use time::OffsetDateTime;
+
+use crate::millis;
+
+/// A snowflake with custom field widths, e.g. trading increment bits for a
+/// longer lifespan with `CustomSnowflake<45, 5, 5, 8>`. The fields are packed
+/// in the same order as [`Snowflake`](crate::Snowflake), from the increment in
+/// the lowest bits up to the timestamp; `CustomSnowflake<42, 5, 5, 12>` is the
+/// same layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomSnowflake<
+  const TIMESTAMP_BITS: u32,
+  const WORKER_BITS: u32,
+  const PROCESS_BITS: u32,
+  const INCREMENT_BITS: u32,
+> {
+  value: u64,
+  epoch: u64,
+}
+
+impl<const TIMESTAMP_BITS: u32, const WORKER_BITS: u32, const PROCESS_BITS: u32, const INCREMENT_BITS: u32>
+  CustomSnowflake<TIMESTAMP_BITS, WORKER_BITS, PROCESS_BITS, INCREMENT_BITS>
+{
+  pub const INCREMENT_MAX: u16 = mask(INCREMENT_BITS) as u16;
+  const LAYOUT: () = assert!(
+    TIMESTAMP_BITS + WORKER_BITS + PROCESS_BITS + INCREMENT_BITS <= u64::BITS
+      && WORKER_BITS <= u8::BITS
+      && PROCESS_BITS <= u8::BITS
+      && INCREMENT_BITS <= u16::BITS,
+    "fields don't fit their types"
+  );
+  pub const PROCESS_MAX: u8 = mask(PROCESS_BITS) as u8;
+  const PROCESS_SHIFT: u32 = INCREMENT_BITS;
+  pub const TIMESTAMP_MAX: u64 = mask(TIMESTAMP_BITS);
+  const TIMESTAMP_SHIFT: u32 = INCREMENT_BITS + PROCESS_BITS + WORKER_BITS;
+  pub const WORKER_MAX: u8 = mask(WORKER_BITS) as u8;
+  const WORKER_SHIFT: u32 = INCREMENT_BITS + PROCESS_BITS;
+
+  pub fn new_with_timestamp_and_epoch(
+    worker: u8,
+    process: u8,
+    increment: u16,
+    timestamp: OffsetDateTime,
+    epoch: u64,
+  ) -> Self {
+    let () = Self::LAYOUT;
+
+    let offset_timestamp_ms = millis(timestamp) - epoch;
+
+    let value = (offset_timestamp_ms & Self::TIMESTAMP_MAX) << Self::TIMESTAMP_SHIFT
+      | (worker as u64 & Self::WORKER_MAX as u64) << Self::WORKER_SHIFT
+      | (process as u64 & Self::PROCESS_MAX as u64) << Self::PROCESS_SHIFT
+      | (increment as u64 & Self::INCREMENT_MAX as u64);
+
+    Self { value, epoch }
+  }
+
+  pub fn from_value_with_epoch(value: u64, epoch: u64) -> Self {
+    let () = Self::LAYOUT;
+
+    Self { value, epoch }
+  }
+
+  pub fn value(&self) -> u64 { self.value }
+
+  pub fn epoch(&self) -> u64 { self.epoch }
+
+  pub fn timestamp(&self) -> u64 { self.value >> Self::TIMESTAMP_SHIFT & Self::TIMESTAMP_MAX }
+
+  pub fn worker(&self) -> u8 { (self.value >> Self::WORKER_SHIFT) as u8 & Self::WORKER_MAX }
+
+  pub fn process(&self) -> u8 { (self.value >> Self::PROCESS_SHIFT) as u8 & Self::PROCESS_MAX }
+
+  pub fn increment(&self) -> u16 { self.value as u16 & Self::INCREMENT_MAX }
+
+  pub fn offset_timestamp(&self) -> u64 { self.timestamp().wrapping_add(self.epoch) }
+}
+
+const fn mask(bits: u32) -> u64 {
+  match bits {
+    0 => 0,
+    bits => u64::MAX >> (u64::BITS - bits),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use time::macros::datetime;
+
+  use super::*;
+  use crate::{Snowflake, AIRDASH_EPOCH};
+
+  const WORKER: u8 = 8;
+  const PROCESS: u8 = 26;
+  const INCREMENT: u16 = 201;
+
+  type LongLived = CustomSnowflake<45, 5, 5, 8>;
+
+  #[test]
+  fn test_default_layout_matches_snowflake() {
+    let timestamp = datetime!(2022-07-08 09:10:11 UTC);
+
+    let custom = CustomSnowflake::<42, 5, 5, 12>::new_with_timestamp_and_epoch(
+      WORKER,
+      PROCESS,
+      INCREMENT,
+      timestamp,
+      AIRDASH_EPOCH,
+    );
+    let snowflake = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, timestamp);
+
+    assert_eq!(custom.value(), snowflake.value());
+    assert_eq!(CustomSnowflake::<42, 5, 5, 12>::TIMESTAMP_MAX, crate::TIMESTAMP_MAX);
+  }
+
+  #[test]
+  fn test_custom_layout() {
+    let timestamp = datetime!(2100-01-01 00:00:00.123 UTC);
+
+    let snowflake = LongLived::new_with_timestamp_and_epoch(WORKER, PROCESS, INCREMENT, timestamp, AIRDASH_EPOCH);
+
+    assert_eq!(snowflake.worker(), WORKER);
+    assert_eq!(snowflake.process(), PROCESS);
+    assert_eq!(snowflake.increment(), INCREMENT);
+    assert_eq!(snowflake.offset_timestamp(), millis(timestamp));
+    assert_eq!(
+      LongLived::from_value_with_epoch(snowflake.value(), AIRDASH_EPOCH),
+      snowflake
+    );
+
+    assert_eq!(LongLived::INCREMENT_MAX, 255);
+    assert!(snowflake.value() <= i64::MAX as u64);
+  }
+
+  #[test]
+  fn test_custom_layout_lifespan() {
+    let last = LongLived::from_value_with_epoch(LongLived::TIMESTAMP_MAX << 18, AIRDASH_EPOCH);
+    let default_last = Snowflake::from_value(u64::MAX);
+
+    assert!(last.offset_timestamp() > default_last.offset_timestamp());
+    assert!(last.offset_timestamp() > millis(datetime!(2084-01-01 00:00:00 UTC)));
+  }
+}
@@ -0,0 +1,104 @@
+//! Well-known epochs, in unix milliseconds.
+
+pub const UNIX: u64 = 0;
+/// 2015-01-01T00:00:00Z.
+pub const DISCORD: u64 = 1420070400000;
+/// 2010-11-04T01:42:54.657Z.
+pub const TWITTER: u64 = 1288834974657;
+pub const AIRDASH: u64 = crate::AIRDASH_EPOCH;
+
+/// Epoch at midnight UTC of a date, checked at compile time:
+///
+/// ```
+/// assert_eq!(snowflake::epoch!(2015-01-01), snowflake::AIRDASH_EPOCH);
+/// ```
+///
+/// ```compile_fail
+/// let epoch = snowflake::epoch!(2015-02-30);
+/// ```
+#[macro_export]
+macro_rules! epoch {
+  ($($date:tt)+) => {{
+    const EPOCH: u64 = $crate::epochs::__epoch_from_date(stringify!($($date)+));
+    EPOCH
+  }};
+}
+
+#[doc(hidden)]
+pub const fn __epoch_from_date(date: &str) -> u64 {
+  const DAYS_IN_MONTH: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+  let date = date.as_bytes();
+  let mut parts = [0u64; 3];
+  let mut part = 0;
+  let mut index = 0;
+
+  while index < date.len() {
+    match date[index] {
+      digit @ b'0'..=b'9' => parts[part] = parts[part] * 10 + (digit - b'0') as u64,
+      b'-' if part < 2 => part += 1,
+      b' ' => {}
+      _ => panic!("expected a date like 2015-01-01"),
+    }
+
+    index += 1;
+  }
+
+  assert!(part == 2, "expected a date like 2015-01-01");
+
+  let [year, month, day] = parts;
+  let is_leap_year = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+
+  assert!(year >= 1970, "epochs before 1970 aren't supported");
+  assert!(month >= 1 && month <= 12, "month is out of range");
+  assert!(
+    day >= 1 && (day <= DAYS_IN_MONTH[month as usize - 1] || (month == 2 && day == 29 && is_leap_year)),
+    "day is out of range"
+  );
+
+  // days since 1970-01-01 by Howard Hinnant's days_from_civil
+  let (year, month) = if month <= 2 {
+    (year - 1, month + 9)
+  } else {
+    (year, month - 3)
+  };
+  let era = year / 400;
+  let year_of_era = year - era * 400;
+  let day_of_year = (153 * month + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+  (era * 146097 + day_of_era - 719468) * 24 * 60 * 60 * 1000
+}
+
+/// Looks up one of the epochs above by its name, ignoring case.
+pub fn epoch_for(name: &str) -> Option<u64> {
+  match name.to_ascii_lowercase().as_str() {
+    "unix" => Some(UNIX),
+    "discord" => Some(DISCORD),
+    "twitter" => Some(TWITTER),
+    "airdash" => Some(AIRDASH),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_epoch_for() {
+    assert_eq!(epoch_for("discord"), Some(DISCORD));
+    assert_eq!(epoch_for("Twitter"), Some(TWITTER));
+    assert_eq!(epoch_for("unix"), Some(UNIX));
+    assert_eq!(epoch_for("airdash"), Some(crate::AIRDASH_EPOCH));
+    assert_eq!(epoch_for("instagram"), None);
+  }
+
+  #[test]
+  fn test_epoch_macro() {
+    assert_eq!(crate::epoch!(2015-01-01), crate::AIRDASH_EPOCH);
+    assert_eq!(crate::epoch!(1970-01-01), UNIX);
+    assert_eq!(crate::epoch!(2000-03-01), 951868800000);
+    assert_eq!(crate::epoch!(2024-02-29), 1709164800000);
+  }
+}
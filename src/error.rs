@@ -0,0 +1,194 @@
+use std::fmt::Display;
+use std::num::ParseIntError;
+
+use crate::TIMESTAMP_BITS;
+
+#[derive(Debug)]
+pub enum SnowflakeError {
+  ForbiddenWorker(u8),
+  Parse(ParseIntError),
+  Io(std::io::Error),
+  FieldOutOfRange { field: &'static str, value: u64, max: u64 },
+  TimestampBeforeEpoch,
+  TimestampOverflow,
+  WorkerProvider(Box<dyn std::error::Error + Send + Sync>),
+  LeaseLost,
+  SequenceExhausted,
+  ClockStalled,
+  IncrementZeroNotReserved,
+  BackfillEvicted,
+  NoProcesses,
+  DuplicateProcess(u8),
+  Empty,
+  InvalidDigit(u8),
+  InvalidLength { expected: usize, found: usize },
+  ValueOverflow,
+}
+
+impl Display for SnowflakeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::ForbiddenWorker(worker) => write!(f, "worker {worker} is forbidden"),
+      Self::Parse(err) => write!(f, "invalid snowflake: {err}"),
+      Self::Io(err) => write!(f, "failed to read snowflake: {err}"),
+      Self::FieldOutOfRange { field, value, max } => write!(f, "{field} {value} is out of range (max {max})"),
+      Self::TimestampBeforeEpoch => write!(f, "timestamp is before the epoch"),
+      Self::TimestampOverflow => write!(
+        f,
+        "timestamp does not fit in {} bits after the epoch",
+        TIMESTAMP_BITS - 1
+      ),
+      Self::WorkerProvider(err) => write!(f, "worker provider failed: {err}"),
+      Self::LeaseLost => write!(f, "worker lease was lost"),
+      Self::SequenceExhausted => write!(f, "all increments of the current millisecond are used up"),
+      Self::ClockStalled => write!(f, "clock stopped advancing"),
+      Self::IncrementZeroNotReserved => write!(f, "increment 0 is not reserved for headers"),
+      Self::BackfillEvicted => write!(f, "increments of this millisecond are no longer tracked"),
+      Self::NoProcesses => write!(f, "at least one process is required"),
+      Self::DuplicateProcess(process) => write!(f, "process {process} is configured more than once"),
+      Self::Empty => write!(f, "invalid snowflake: no digits"),
+      Self::InvalidDigit(byte) => write!(f, "invalid snowflake: invalid digit '{}'", byte.escape_ascii()),
+      Self::InvalidLength { expected, found } => {
+        write!(f, "invalid snowflake: expected {expected} digits, found {found}")
+      }
+      Self::ValueOverflow => write!(f, "invalid snowflake: value does not fit in 64 bits"),
+    }
+  }
+}
+
+impl std::error::Error for SnowflakeError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Parse(err) => Some(err),
+      Self::Io(err) => Some(err),
+      Self::WorkerProvider(err) => Some(err.as_ref()),
+      _ => None,
+    }
+  }
+}
+
+impl From<ParseIntError> for SnowflakeError {
+  fn from(err: ParseIntError) -> Self { Self::Parse(err) }
+}
+
+impl From<std::io::Error> for SnowflakeError {
+  fn from(err: std::io::Error) -> Self { Self::Io(err) }
+}
+
+/// Error of [`Snowflake::parse_discord_url`](crate::Snowflake::parse_discord_url).
+#[derive(Debug)]
+pub enum UrlParseError {
+  NotAMessageUrl,
+  InvalidId(SnowflakeError),
+}
+
+impl Display for UrlParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::NotAMessageUrl => write!(f, "not a discord message url"),
+      Self::InvalidId(err) => write!(f, "invalid id in discord url: {err}"),
+    }
+  }
+}
+
+impl std::error::Error for UrlParseError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::NotAMessageUrl => None,
+      Self::InvalidId(err) => Some(err),
+    }
+  }
+}
+
+impl From<SnowflakeError> for UrlParseError {
+  fn from(err: SnowflakeError) -> Self { Self::InvalidId(err) }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::ErrorKind;
+
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    let parse = "abc".parse::<u64>().unwrap_err();
+
+    assert_eq!(SnowflakeError::ForbiddenWorker(0).to_string(), "worker 0 is forbidden");
+    assert_eq!(
+      SnowflakeError::Parse(parse).to_string(),
+      "invalid snowflake: invalid digit found in string"
+    );
+    assert_eq!(
+      SnowflakeError::Io(ErrorKind::UnexpectedEof.into()).to_string(),
+      "failed to read snowflake: unexpected end of file"
+    );
+    assert_eq!(
+      SnowflakeError::FieldOutOfRange {
+        field: "worker",
+        value: 32,
+        max: 31,
+      }
+      .to_string(),
+      "worker 32 is out of range (max 31)"
+    );
+    assert_eq!(
+      SnowflakeError::TimestampBeforeEpoch.to_string(),
+      "timestamp is before the epoch"
+    );
+    assert_eq!(
+      SnowflakeError::TimestampOverflow.to_string(),
+      "timestamp does not fit in 41 bits after the epoch"
+    );
+    assert_eq!(
+      SnowflakeError::WorkerProvider("lease unavailable".into()).to_string(),
+      "worker provider failed: lease unavailable"
+    );
+    assert_eq!(SnowflakeError::LeaseLost.to_string(), "worker lease was lost");
+    assert_eq!(
+      SnowflakeError::SequenceExhausted.to_string(),
+      "all increments of the current millisecond are used up"
+    );
+    assert_eq!(SnowflakeError::ClockStalled.to_string(), "clock stopped advancing");
+    assert_eq!(
+      SnowflakeError::IncrementZeroNotReserved.to_string(),
+      "increment 0 is not reserved for headers"
+    );
+    assert_eq!(
+      SnowflakeError::BackfillEvicted.to_string(),
+      "increments of this millisecond are no longer tracked"
+    );
+    assert_eq!(
+      SnowflakeError::NoProcesses.to_string(),
+      "at least one process is required"
+    );
+    assert_eq!(
+      SnowflakeError::DuplicateProcess(7).to_string(),
+      "process 7 is configured more than once"
+    );
+    assert_eq!(SnowflakeError::Empty.to_string(), "invalid snowflake: no digits");
+    assert_eq!(
+      SnowflakeError::InvalidDigit(0xFF).to_string(),
+      r"invalid snowflake: invalid digit '\xff'"
+    );
+    assert_eq!(
+      SnowflakeError::InvalidLength { expected: 11, found: 3 }.to_string(),
+      "invalid snowflake: expected 11 digits, found 3"
+    );
+    assert_eq!(
+      SnowflakeError::ValueOverflow.to_string(),
+      "invalid snowflake: value does not fit in 64 bits"
+    );
+  }
+
+  #[test]
+  fn test_url_parse_error_display() {
+    let parse = "abc".parse::<u64>().unwrap_err();
+
+    assert_eq!(UrlParseError::NotAMessageUrl.to_string(), "not a discord message url");
+    assert_eq!(
+      UrlParseError::InvalidId(SnowflakeError::Parse(parse)).to_string(),
+      "invalid id in discord url: invalid snowflake: invalid digit found in string"
+    );
+  }
+}
@@ -0,0 +1,50 @@
+use crate::Snowflake;
+
+/// Decodes raw integers, e.g. from a database, without wrapping them in a
+/// [`Snowflake`] first. They're read with
+/// [`AIRDASH_EPOCH`](crate::AIRDASH_EPOCH) like [`Snowflake::from_value`].
+pub trait SnowflakeExt: Copy {
+  fn to_snowflake(self) -> Snowflake;
+
+  fn worker(self) -> u8 { self.to_snowflake().worker() }
+
+  fn process(self) -> u8 { self.to_snowflake().process() }
+
+  fn increment(self) -> u16 { self.to_snowflake().increment() }
+
+  /// Timestamp relative to the epoch, like [`Snowflake::timestamp`].
+  fn timestamp_raw(self) -> u64 { self.to_snowflake().timestamp() }
+}
+
+impl SnowflakeExt for u64 {
+  fn to_snowflake(self) -> Snowflake { self.into() }
+}
+
+impl SnowflakeExt for i64 {
+  fn to_snowflake(self) -> Snowflake { self.into() }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_u64() {
+    let raw = 175928847299117063u64;
+
+    assert_eq!(123u64.to_snowflake(), Snowflake::from_value(123));
+    assert_eq!(raw.worker(), 1);
+    assert_eq!(raw.process(), 0);
+    assert_eq!(raw.increment(), 7);
+    assert_eq!(raw.timestamp_raw(), 41944705796);
+  }
+
+  #[test]
+  fn test_i64() {
+    let raw = 175928847299117063i64;
+
+    assert_eq!(raw.to_snowflake(), Snowflake::from_value(175928847299117063));
+    assert_eq!((raw.worker(), raw.process(), raw.increment()), (1, 0, 7));
+    assert_eq!(raw.timestamp_raw(), 175928847299117063u64.timestamp_raw());
+  }
+}
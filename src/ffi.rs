@@ -0,0 +1,90 @@
+//! C interface for generating and decoding snowflakes.
+//!
+//! A generator isn't synchronized: it may be moved between threads, but it
+//! must not be used from several threads at the same time. Use one generator
+//! per thread (with distinct processes) or guard it with a lock.
+
+use crate::{Snowflake, SnowflakeGenerator};
+
+/// Creates a generator, to be released with [`snowflake_generator_free`].
+/// Returns null if `worker` or `process` is out of range.
+#[no_mangle]
+pub extern "C" fn snowflake_generator_new(worker: u8, process: u8, epoch: u64) -> *mut SnowflakeGenerator {
+  match SnowflakeGenerator::builder()
+    .worker(worker)
+    .process(process)
+    .epoch(epoch)
+    .build()
+  {
+    Ok(generator) => Box::into_raw(Box::new(generator)),
+    Err(_) => std::ptr::null_mut(),
+  }
+}
+
+/// # Safety
+///
+/// `generator` must come from [`snowflake_generator_new`], not be freed yet
+/// and not be used by another thread during the call.
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_generator_generate(generator: *mut SnowflakeGenerator) -> u64 {
+  (*generator).generate().value()
+}
+
+/// # Safety
+///
+/// `generator` must come from [`snowflake_generator_new`] and not be freed
+/// yet, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_generator_free(generator: *mut SnowflakeGenerator) {
+  if !generator.is_null() {
+    drop(Box::from_raw(generator));
+  }
+}
+
+#[no_mangle]
+pub extern "C" fn snowflake_worker(value: u64) -> u8 { Snowflake::from_value(value).worker() }
+
+#[no_mangle]
+pub extern "C" fn snowflake_process(value: u64) -> u8 { Snowflake::from_value(value).process() }
+
+#[no_mangle]
+pub extern "C" fn snowflake_increment(value: u64) -> u16 { Snowflake::from_value(value).increment() }
+
+/// Milliseconds since the epoch the snowflake was generated with.
+#[no_mangle]
+pub extern "C" fn snowflake_timestamp(value: u64) -> u64 { Snowflake::from_value(value).timestamp() }
+
+#[cfg(test)]
+mod tests {
+  use time::OffsetDateTime;
+
+  use super::*;
+  use crate::{millis, AIRDASH_EPOCH};
+
+  #[test]
+  fn test_generate() {
+    let start = millis(OffsetDateTime::now_utc());
+
+    let generator = snowflake_generator_new(8, 26, AIRDASH_EPOCH);
+
+    let first = unsafe { snowflake_generator_generate(generator) };
+    let second = unsafe { snowflake_generator_generate(generator) };
+
+    unsafe { snowflake_generator_free(generator) };
+
+    assert_ne!(first, second);
+    assert_eq!(snowflake_worker(first), 8);
+    assert_eq!(snowflake_process(first), 26);
+    assert_eq!(snowflake_increment(first), 0);
+    assert!(snowflake_timestamp(first) + AIRDASH_EPOCH >= start);
+  }
+
+  #[test]
+  fn test_out_of_range() {
+    assert!(snowflake_generator_new(32, 26, AIRDASH_EPOCH).is_null());
+    assert!(snowflake_generator_new(8, 32, AIRDASH_EPOCH).is_null());
+  }
+
+  #[test]
+  fn test_free_null() { unsafe { snowflake_generator_free(std::ptr::null_mut()) }; }
+}
@@ -1,31 +1,483 @@
-use crate::{Snowflake, AIRDASH_EPOCH};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use time::OffsetDateTime;
+
+use crate::snowflake::check_field;
+use crate::{
+  millis, ClockSource, ManualClock, Snowflake, SnowflakeError, SystemClock, AIRDASH_EPOCH, INCREMENT_MAX, PROCESS_MAX,
+  WORKER_MAX,
+};
+
+/// What the generator does once all increments of the current millisecond
+/// are used up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExhaustionPolicy {
+  /// Busy-wait for the next millisecond.
+  #[default]
+  SpinWait,
+  /// Sleep until the next millisecond.
+  SleepUntilNextMs,
+  /// Fail with [`SnowflakeError::SequenceExhausted`].
+  Error,
+}
+
+/// Outcome of [`SnowflakeGenerator::self_test`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfTestResult {
+  pub generated: u64,
+  /// Snowflakes generated more than once, which should never happen.
+  pub duplicates: u64,
+  pub rate_per_sec: f64,
+}
 
 #[derive(Debug)]
-pub struct SnowflakeGenerator {
+pub struct SnowflakeGenerator<C = SystemClock> {
   worker: u8,
   process: u8,
   increment: u16,
+  increment_floor: u16,
+  exhaustion_policy: ExhaustionPolicy,
+  max_spin: Option<Duration>,
   epoch: u64,
+  last_timestamp: u64,
+  /// Whether increment 0 is kept for `generate_header`.
+  reserve_increment_zero: bool,
+  /// Unix millisecond of the last `generate_header` snowflake.
+  header_timestamp: Option<u64>,
+  /// Next increment for the latest unix milliseconds passed to
+  /// `generate_at`, at most `BACKFILL_CAPACITY` of them.
+  backfill_increments: BTreeMap<u64, u16>,
+  /// Latest unix millisecond evicted from `backfill_increments`.
+  backfill_evicted: Option<u64>,
+  /// Set by [`SnowflakeGenerator::monotonic`]: exhausting a millisecond
+  /// moves on to the next one instead of waiting for the clock.
+  monotonic: bool,
+  /// Mark passed to `with_high_water_mark`, which behaves like `monotonic`
+  /// until the clock passes it.
+  resume_after: u64,
+  clock: C,
 }
 
 impl SnowflakeGenerator {
+  pub fn builder() -> SnowflakeGeneratorBuilder { SnowflakeGeneratorBuilder::default() }
+
   pub fn new(worker: u8, process: u8) -> Self { Self::new_with_epoch(worker, process, AIRDASH_EPOCH) }
 
+  /// Packs `region` (0..=3) into the high 2 bits and `local_process` (0..=7)
+  /// into the low 3 bits of the process field, see [`Snowflake::region`] and
+  /// [`Snowflake::local_process`]. Fails if either is out of range.
+  pub fn new_with_region(worker: u8, region: u8, local_process: u8, epoch: u64) -> Result<Self, SnowflakeError> {
+    check_field("region", region.into(), 0b11)?;
+    check_field("local process", local_process.into(), 0b111)?;
+
+    Self::builder()
+      .worker(worker)
+      .process(region << 3 | local_process)
+      .epoch(epoch)
+      .build()
+  }
+
+  /// Obtains the worker from `provider`, e.g. a lease from a coordination
+  /// service, and validates it. Renewing the lease is up to the caller.
+  pub fn with_worker_provider<E>(
+    provider: impl FnOnce() -> Result<u8, E>,
+    process: u8,
+    epoch: u64,
+  ) -> Result<Self, SnowflakeError>
+  where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+  {
+    let worker = provider().map_err(|err| SnowflakeError::WorkerProvider(err.into()))?;
+
+    Self::builder().worker(worker).process(process).epoch(epoch).build()
+  }
+
+  /// Generator with a process assigned to the calling thread. Threads get
+  /// processes in the order they first call this, starting at 0, and keep
+  /// them until the program exits, so at most 32 threads can call it. Calling
+  /// it again on the same thread returns a generator for the same node, so
+  /// keep one generator per thread.
+  pub fn for_current_thread(worker: u8, epoch: u64) -> Result<Self, SnowflakeError> {
+    static NEXT_PROCESS: AtomicU8 = AtomicU8::new(0);
+
+    thread_local! {
+      static PROCESS: Cell<Option<u8>> = const { Cell::new(None) };
+    }
+
+    let process = match PROCESS.get() {
+      Some(process) => process,
+      None => {
+        let process = NEXT_PROCESS
+          .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| next.checked_add(1))
+          .unwrap_or(u8::MAX);
+        check_field("process", process.into(), PROCESS_MAX.into())?;
+
+        PROCESS.set(Some(process));
+        process
+      }
+    };
+
+    Self::builder().worker(worker).process(process).epoch(epoch).build()
+  }
+
+  /// Only generates snowflakes for unix milliseconds after `last_seen_ms`,
+  /// e.g. a [`high_water_mark`](Self::high_water_mark) persisted by a previous
+  /// instance, to survive the clock going back between restarts. While the
+  /// clock is behind `last_seen_ms`, the timestamps run ahead of it instead
+  /// of waiting for it to catch up.
+  pub fn with_high_water_mark(worker: u8, process: u8, epoch: u64, last_seen_ms: u64) -> Self {
+    Self {
+      last_timestamp: last_seen_ms + 1,
+      resume_after: last_seen_ms,
+      ..Self::new_with_epoch(worker, process, epoch)
+    }
+  }
+
+  /// Generates snowflakes without reading the wall clock, starting at unix
+  /// millisecond `start_ms`. Each millisecond is filled with every increment
+  /// before moving on to the next one, so the snowflakes are strictly
+  /// increasing even if the system time is unreliable. The timestamps don't
+  /// reflect when the snowflakes were created.
+  pub fn monotonic(worker: u8, process: u8, start_ms: u64) -> SnowflakeGenerator<ManualClock> {
+    SnowflakeGenerator {
+      monotonic: true,
+      ..Self::new(worker, process)
+    }
+    .with_clock(ManualClock::new(start_ms))
+  }
+
   pub fn new_with_epoch(worker: u8, process: u8, epoch: u64) -> Self {
     Self {
       epoch,
       worker,
       process,
       increment: 0,
+      increment_floor: 0,
+      exhaustion_policy: ExhaustionPolicy::default(),
+      max_spin: None,
+      last_timestamp: 0,
+      reserve_increment_zero: false,
+      header_timestamp: None,
+      backfill_increments: BTreeMap::new(),
+      backfill_evicted: None,
+      monotonic: false,
+      resume_after: 0,
+      clock: SystemClock,
+    }
+  }
+}
+
+impl<C: ClockSource> SnowflakeGenerator<C> {
+  /// How many milliseconds [`generate_at`](Self::generate_at) tracks the
+  /// increments of.
+  pub const BACKFILL_CAPACITY: usize = 1_024;
+  pub const MAX_CLOCK_SKEW_MS: u64 = 60_000;
+
+  #[cfg(feature = "async")]
+  pub(crate) fn with_exhaustion_policy(self, exhaustion_policy: ExhaustionPolicy) -> Self {
+    Self {
+      exhaustion_policy,
+      ..self
+    }
+  }
+
+  pub fn with_clock<T: ClockSource>(self, clock: T) -> SnowflakeGenerator<T> {
+    SnowflakeGenerator {
+      worker: self.worker,
+      process: self.process,
+      increment: self.increment,
+      increment_floor: self.increment_floor,
+      exhaustion_policy: self.exhaustion_policy,
+      max_spin: self.max_spin,
+      epoch: self.epoch,
+      last_timestamp: self.last_timestamp,
+      reserve_increment_zero: self.reserve_increment_zero,
+      header_timestamp: self.header_timestamp,
+      backfill_increments: self.backfill_increments,
+      backfill_evicted: self.backfill_evicted,
+      monotonic: self.monotonic,
+      resume_after: self.resume_after,
+      clock,
     }
   }
 
+  /// The increment restarts at its floor (0 by default) only once the clock
+  /// reports a strictly later millisecond than the last generated snowflake.
+  /// If the sequence of the current millisecond is exhausted, this waits for
+  /// the clock to advance as configured by the [`ExhaustionPolicy`].
+  ///
+  /// Panics where [`try_generate`](Self::try_generate) would fail.
   pub fn generate(&mut self) -> Snowflake {
-    let snowflake = Snowflake::new_with_epoch(self.worker, self.process, self.increment, self.epoch);
+    match self.try_generate() {
+      Ok(snowflake) => snowflake,
+      Err(err) => panic!("{err}"),
+    }
+  }
+
+  pub fn try_generate(&mut self) -> Result<Snowflake, SnowflakeError> {
+    debug_assert!(self.worker <= WORKER_MAX, "worker {} is out of range", self.worker);
+    debug_assert!(self.process <= PROCESS_MAX, "process {} is out of range", self.process);
+
+    let mut timestamp = self.clock.millis();
+
+    if timestamp <= self.last_timestamp && self.increment > INCREMENT_MAX {
+      if self.exhaustion_policy == ExhaustionPolicy::Error && !self.runs_ahead() {
+        return Err(SnowflakeError::SequenceExhausted);
+      }
+
+      timestamp = self.wait_until_after(self.last_timestamp)?;
+    }
+
+    if timestamp > self.last_timestamp {
+      self.last_timestamp = timestamp;
+      self.increment = self.increment_floor;
+    }
+
+    let snowflake = Snowflake::from_unix_millis(
+      self.worker,
+      self.process,
+      self.increment,
+      self.last_timestamp,
+      self.epoch,
+    );
+
+    debug_assert_eq!(snowflake.worker(), self.worker);
+    debug_assert_eq!(snowflake.process(), self.process);
 
-    self.increment = self.increment.wrapping_add(1);
+    self.increment += 1;
 
-    snowflake
+    Ok(snowflake)
+  }
+
+  /// The snowflake [`generate`](Self::generate) would return now, without
+  /// advancing the generator. It may return a different one if the clock
+  /// ticks over in between. If the current millisecond is exhausted, this
+  /// assumes the next snowflake is generated in the following millisecond.
+  pub fn peek(&self) -> Snowflake {
+    let timestamp = self.clock.millis();
+
+    let (timestamp, increment) = if timestamp > self.last_timestamp {
+      (timestamp, self.increment_floor)
+    } else if self.increment > INCREMENT_MAX {
+      (self.last_timestamp + 1, self.increment_floor)
+    } else {
+      (self.last_timestamp, self.increment)
+    };
+
+    Snowflake::from_unix_millis(self.worker, self.process, increment, timestamp, self.epoch)
+  }
+
+  /// Generates the increment 0 snowflake of the current millisecond, or of
+  /// the next one if this millisecond already has its header.
+  ///
+  /// Panics where [`try_generate_header`](Self::try_generate_header) would
+  /// fail.
+  pub fn generate_header(&mut self) -> Snowflake {
+    match self.try_generate_header() {
+      Ok(snowflake) => snowflake,
+      Err(err) => panic!("{err}"),
+    }
+  }
+
+  /// Fails with [`SnowflakeError::IncrementZeroNotReserved`] unless the
+  /// generator was built with
+  /// [`reserve_increment_zero`](SnowflakeGeneratorBuilder::reserve_increment_zero),
+  /// which keeps [`generate`](Self::generate) from using increment 0.
+  pub fn try_generate_header(&mut self) -> Result<Snowflake, SnowflakeError> {
+    if !self.reserve_increment_zero {
+      return Err(SnowflakeError::IncrementZeroNotReserved);
+    }
+
+    let mut timestamp = self.clock.millis().max(self.last_timestamp);
+
+    if self.header_timestamp == Some(timestamp) {
+      timestamp = self.wait_until_after(timestamp)?;
+    }
+
+    if timestamp > self.last_timestamp {
+      self.last_timestamp = timestamp;
+      self.increment = self.increment_floor;
+    }
+
+    self.header_timestamp = Some(timestamp);
+
+    Ok(Snowflake::from_unix_millis(
+      self.worker,
+      self.process,
+      0,
+      timestamp,
+      self.epoch,
+    ))
+  }
+
+  /// Generates a snowflake for `timestamp` instead of now, e.g. to backfill
+  /// historical data. The increments are tracked separately from
+  /// [`generate`](Self::generate), so only use timestamps this node never
+  /// generates live snowflakes for. Only the latest
+  /// [`BACKFILL_CAPACITY`](Self::BACKFILL_CAPACITY) milliseconds are
+  /// tracked, older ones fail with [`SnowflakeError::BackfillEvicted`]
+  /// instead of reissuing their increments.
+  pub fn generate_at(&mut self, timestamp: OffsetDateTime) -> Result<Snowflake, SnowflakeError> {
+    let timestamp_ms = millis(timestamp);
+
+    if self.backfill_evicted.is_some_and(|evicted| timestamp_ms <= evicted) {
+      return Err(SnowflakeError::BackfillEvicted);
+    }
+
+    let increment = self
+      .backfill_increments
+      .get(&timestamp_ms)
+      .copied()
+      .unwrap_or(self.increment_floor);
+
+    if increment > INCREMENT_MAX {
+      return Err(SnowflakeError::SequenceExhausted);
+    }
+
+    let snowflake =
+      Snowflake::try_new_with_timestamp_and_epoch(self.worker, self.process, increment, timestamp, self.epoch)?;
+
+    self.backfill_increments.insert(timestamp_ms, increment + 1);
+
+    if self.backfill_increments.len() > Self::BACKFILL_CAPACITY {
+      self.backfill_evicted = self.backfill_increments.pop_first().map(|(evicted, _)| evicted);
+    }
+
+    Ok(snowflake)
+  }
+
+  /// Replaces the contents of `buf` with `n` new snowflakes, reusing its
+  /// allocation if it has the capacity.
+  pub fn generate_into(&mut self, buf: &mut Vec<Snowflake>, n: usize) {
+    buf.clear();
+    buf.extend((0..n).map(|_| self.generate()));
+  }
+
+  /// Generates snowflakes for `duration`, e.g. to check on startup that this
+  /// machine reaches the required rate. The snowflakes are discarded.
+  pub fn self_test(&mut self, duration: Duration) -> SelfTestResult {
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let started = Instant::now();
+
+    while started.elapsed() < duration {
+      if !seen.insert(self.generate()) {
+        duplicates += 1;
+      }
+    }
+
+    let generated = seen.len() as u64 + duplicates;
+
+    SelfTestResult {
+      generated,
+      duplicates,
+      rate_per_sec: generated as f64 / started.elapsed().as_secs_f64(),
+    }
+  }
+
+  /// Pairs every snowflake with the wall time right after it was generated.
+  pub fn with_wall_clock(self) -> impl Iterator<Item = (Snowflake, SystemTime)> {
+    self.map(|snowflake| (snowflake, SystemTime::now()))
+  }
+
+  pub fn enumerated(self) -> impl Iterator<Item = (usize, Snowflake)> { self.enumerate() }
+
+  /// The unix millisecond of the latest snowflake generated, to be persisted
+  /// for [`with_high_water_mark`](SnowflakeGenerator::with_high_water_mark).
+  pub fn high_water_mark(&self) -> u64 { self.last_timestamp }
+
+  /// Whether both generators run on the same node, so they could produce the
+  /// same snowflakes.
+  pub fn collides_with<T>(&self, other: &SnowflakeGenerator<T>) -> bool {
+    self.worker == other.worker && self.process == other.process
+  }
+
+  /// Whether `id` could come from this generator: it has to be from this node
+  /// and, decoded with this generator's epoch, not be created more than
+  /// [`MAX_CLOCK_SKEW_MS`](Self::MAX_CLOCK_SKEW_MS) in the future. The epoch
+  /// attached to `id` is ignored, as parsing always attaches
+  /// [`AIRDASH_EPOCH`].
+  pub fn could_have_produced(&self, id: &Snowflake) -> bool {
+    let id = Snowflake::from_value_with_epoch(id.value(), self.epoch);
+
+    id.worker() == self.worker
+      && id.process() == self.process
+      && id.offset_timestamp() <= self.clock.millis().max(self.last_timestamp) + Self::MAX_CLOCK_SKEW_MS
+  }
+
+  /// Blocks until the clock ticks over to the next millisecond, so the next
+  /// snowflake can't collide with one a previous instance of this node
+  /// generated in the current millisecond.
+  pub fn wait_for_next_millisecond(&mut self) {
+    let timestamp = self.clock.millis().max(self.last_timestamp);
+
+    self.last_timestamp = timestamp;
+    self.increment = INCREMENT_MAX + 1;
+
+    // if the clock stalled, the next `try_generate` reports it
+    let _ = self.wait_until_after(timestamp);
+  }
+
+  /// Whether an exhausted millisecond moves on to the next one right away,
+  /// see [`monotonic`](SnowflakeGenerator::monotonic) and
+  /// [`with_high_water_mark`](SnowflakeGenerator::with_high_water_mark).
+  fn runs_ahead(&self) -> bool { self.monotonic || self.clock.millis() <= self.resume_after }
+
+  fn wait_until_after(&self, timestamp: u64) -> Result<u64, SnowflakeError> {
+    if self.runs_ahead() {
+      return Ok(timestamp + 1);
+    }
+
+    let started = Instant::now();
+
+    loop {
+      let now = self.clock.millis();
+
+      if now > timestamp {
+        return Ok(now);
+      }
+
+      if self.max_spin.is_some_and(|max_spin| started.elapsed() >= max_spin) {
+        return Err(SnowflakeError::ClockStalled);
+      }
+
+      match self.exhaustion_policy {
+        // clocks that jumped back would oversleep, so wake up every millisecond
+        ExhaustionPolicy::SleepUntilNextMs => {
+          let left = self
+            .max_spin
+            .map_or(Duration::MAX, |max_spin| max_spin.saturating_sub(started.elapsed()));
+          sleep(Duration::from_millis(1).min(left))
+        }
+        _ => spin_loop(),
+      }
+    }
+  }
+}
+
+impl<C: ClockSource + Send + 'static> SnowflakeGenerator<C> {
+  /// Generates snowflakes on a new thread and sends them to `sender` until
+  /// the receiver is dropped. Once the channel's bound is reached, the thread
+  /// blocks until the receiver catches up.
+  pub fn spawn_producer(mut self, sender: SyncSender<Snowflake>) -> JoinHandle<()> {
+    thread::spawn(move || while sender.send(self.generate()).is_ok() {})
+  }
+}
+
+impl<C> Display for SnowflakeGenerator<C> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "SnowflakeGenerator(worker={}, process={}, epoch={})",
+      self.worker, self.process, self.epoch
+    )
   }
 }
 
@@ -33,25 +485,163 @@ impl Default for SnowflakeGenerator {
   fn default() -> Self { Self::new(0, 0) }
 }
 
-impl Iterator for SnowflakeGenerator {
+#[derive(Debug)]
+pub struct SnowflakeGeneratorBuilder {
+  worker: u8,
+  process: u8,
+  epoch: u64,
+  increment_floor: u16,
+  reserve_increment_zero: bool,
+  exhaustion_policy: ExhaustionPolicy,
+  max_spin: Option<Duration>,
+  forbidden_workers: Vec<u8>,
+}
+
+impl SnowflakeGeneratorBuilder {
+  pub fn worker(mut self, worker: u8) -> Self {
+    self.worker = worker;
+    self
+  }
+
+  pub fn process(mut self, process: u8) -> Self {
+    self.process = process;
+    self
+  }
+
+  pub fn epoch(mut self, epoch: u64) -> Self {
+    self.epoch = epoch;
+    self
+  }
+
+  /// Starts the increment of every millisecond at `floor` instead of 0, e.g.
+  /// to reserve the lower increments for another lane. This leaves
+  /// `INCREMENT_MAX - floor + 1` snowflakes per millisecond.
+  pub fn increment_floor(mut self, floor: u16) -> Self {
+    self.increment_floor = floor;
+    self
+  }
+
+  /// Keeps increment 0 of every millisecond for
+  /// [`generate_header`](SnowflakeGenerator::generate_header), by raising the
+  /// [`increment_floor`](Self::increment_floor) to at least 1.
+  pub fn reserve_increment_zero(mut self, reserve: bool) -> Self {
+    self.reserve_increment_zero = reserve;
+    self
+  }
+
+  pub fn exhaustion_policy(mut self, policy: ExhaustionPolicy) -> Self {
+    self.exhaustion_policy = policy;
+    self
+  }
+
+  /// Makes the generator fail with [`SnowflakeError::ClockStalled`] instead
+  /// of waiting longer than `max_spin` for the clock to advance.
+  pub fn max_spin(mut self, max_spin: Duration) -> Self {
+    self.max_spin = Some(max_spin);
+    self
+  }
+
+  /// Makes [`build`](Self::build) fail if the configured worker is `worker`,
+  /// e.g. for ids reserved for another role.
+  pub fn forbid_worker(mut self, worker: u8) -> Self {
+    self.forbidden_workers.push(worker);
+    self
+  }
+
+  pub fn build(self) -> Result<SnowflakeGenerator, SnowflakeError> {
+    check_field("worker", self.worker.into(), WORKER_MAX.into())?;
+    check_field("process", self.process.into(), PROCESS_MAX.into())?;
+    check_field("increment floor", self.increment_floor.into(), INCREMENT_MAX.into())?;
+
+    if self.forbidden_workers.contains(&self.worker) {
+      return Err(SnowflakeError::ForbiddenWorker(self.worker));
+    }
+
+    Ok(SnowflakeGenerator {
+      increment_floor: self.increment_floor.max(self.reserve_increment_zero.into()),
+      reserve_increment_zero: self.reserve_increment_zero,
+      exhaustion_policy: self.exhaustion_policy,
+      max_spin: self.max_spin,
+      ..SnowflakeGenerator::new_with_epoch(self.worker, self.process, self.epoch)
+    })
+  }
+}
+
+impl Default for SnowflakeGeneratorBuilder {
+  fn default() -> Self {
+    Self {
+      worker: 0,
+      process: 0,
+      epoch: AIRDASH_EPOCH,
+      increment_floor: 0,
+      reserve_increment_zero: false,
+      exhaustion_policy: ExhaustionPolicy::default(),
+      max_spin: None,
+      forbidden_workers: Vec::new(),
+    }
+  }
+}
+
+/// Never ends, so limit it with e.g. [`take`](Iterator::take) before
+/// collecting.
+impl<C: ClockSource> Iterator for SnowflakeGenerator<C> {
   type Item = Snowflake;
 
   fn next(&mut self) -> Option<Self::Item> { Some(self.generate()) }
+
+  fn size_hint(&self) -> (usize, Option<usize>) { (usize::MAX, None) }
 }
 
 #[cfg(test)]
 mod tests {
-  use std::collections::HashSet;
-
-  use time::OffsetDateTime;
+  use time::macros::datetime;
 
   use super::*;
-  use crate::millis;
 
   const WORKER: u8 = 8;
   const PROCESS: u8 = 26;
   const GENERATED_COUNT: usize = 500_000;
 
+  struct TestClock(Cell<u64>);
+
+  impl ClockSource for TestClock {
+    fn millis(&self) -> u64 { self.0.get() }
+  }
+
+  /// Frozen for the first `frozen_reads` reads, then a millisecond later.
+  struct ThawingClock {
+    millis: u64,
+    frozen_reads: Cell<usize>,
+  }
+
+  impl ClockSource for ThawingClock {
+    fn millis(&self) -> u64 {
+      match self.frozen_reads.get() {
+        0 => self.millis + 1,
+        reads => {
+          self.frozen_reads.set(reads - 1);
+          self.millis
+        }
+      }
+    }
+  }
+
+  fn exhausted_generator(policy: ExhaustionPolicy, clock: &ThawingClock) -> SnowflakeGenerator<&ThawingClock> {
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .exhaustion_policy(policy)
+      .build()
+      .unwrap()
+      .with_clock(clock);
+
+    for increment in 0..=INCREMENT_MAX {
+      assert_eq!(generator.try_generate().unwrap().increment(), increment);
+    }
+
+    generator
+  }
+
   #[test]
   fn test_generates_no_duplicates() {
     let generator = SnowflakeGenerator::new(WORKER, PROCESS);
@@ -83,4 +673,641 @@ mod tests {
       assert!(snowflake.offset_timestamp() >= millis(start_time));
     }
   }
+
+  #[test]
+  fn test_builder() {
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .forbid_worker(0)
+      .build()
+      .unwrap();
+
+    let snowflake = generator.generate();
+
+    assert_eq!(snowflake.worker(), WORKER);
+    assert_eq!(snowflake.process(), PROCESS);
+    assert_eq!(snowflake.epoch(), AIRDASH_EPOCH);
+  }
+
+  #[test]
+  fn test_builder_forbidden_worker() {
+    let result = SnowflakeGenerator::builder()
+      .worker(0)
+      .process(PROCESS)
+      .forbid_worker(0)
+      .build();
+
+    assert!(matches!(result, Err(SnowflakeError::ForbiddenWorker(0))));
+  }
+
+  #[test]
+  fn test_builder_out_of_range() {
+    assert!(matches!(
+      SnowflakeGenerator::builder().worker(32).build(),
+      Err(SnowflakeError::FieldOutOfRange { field: "worker", .. })
+    ));
+    assert!(matches!(
+      SnowflakeGenerator::builder().process(32).build(),
+      Err(SnowflakeError::FieldOutOfRange { field: "process", .. })
+    ));
+  }
+
+  #[test]
+  fn test_increment_continues_within_millisecond() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock);
+
+    let first = generator.generate();
+    let second = generator.generate();
+
+    assert_eq!(first.timestamp(), second.timestamp());
+    assert_eq!(first.increment(), 0);
+    assert_eq!(second.increment(), 1);
+  }
+
+  #[test]
+  fn test_increment_resets_on_later_millisecond() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock);
+
+    generator.generate();
+    generator.generate();
+
+    clock.0.set(AIRDASH_EPOCH + 999);
+    let behind = generator.generate();
+
+    clock.0.set(AIRDASH_EPOCH + 1_001);
+    let ahead = generator.generate();
+
+    assert_eq!(behind.timestamp(), 1_000);
+    assert_eq!(behind.increment(), 2);
+    assert_eq!(ahead.timestamp(), 1_001);
+    assert_eq!(ahead.increment(), 0);
+  }
+
+  #[test]
+  fn test_new_with_region() {
+    for region in 0..=3 {
+      for local_process in 0..=7 {
+        let mut generator = SnowflakeGenerator::new_with_region(WORKER, region, local_process, AIRDASH_EPOCH).unwrap();
+
+        let snowflake = generator.generate();
+
+        assert_eq!(snowflake.worker(), WORKER);
+        assert_eq!(snowflake.region(), region);
+        assert_eq!(snowflake.local_process(), local_process);
+      }
+    }
+
+    assert!(matches!(
+      SnowflakeGenerator::new_with_region(WORKER, 4, 0, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange {
+        field: "region",
+        value: 4,
+        max: 3
+      })
+    ));
+    assert!(matches!(
+      SnowflakeGenerator::new_with_region(WORKER, 0, 8, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange {
+        field: "local process",
+        value: 8,
+        max: 7
+      })
+    ));
+  }
+
+  #[test]
+  fn test_wait_for_next_millisecond() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    let construction = millis(OffsetDateTime::now_utc());
+    generator.wait_for_next_millisecond();
+
+    let snowflake = generator.generate();
+
+    assert_eq!(snowflake.increment(), 0);
+    assert!(snowflake.offset_timestamp() > construction);
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  #[should_panic(expected = "worker 32 is out of range")]
+  fn test_generate_out_of_range_worker() {
+    let mut generator = SnowflakeGenerator::new(WORKER_MAX + 1, PROCESS);
+
+    generator.generate();
+  }
+
+  #[test]
+  fn test_with_worker_provider() {
+    let mut generator =
+      SnowflakeGenerator::with_worker_provider(|| Ok::<_, SnowflakeError>(WORKER), PROCESS, AIRDASH_EPOCH).unwrap();
+
+    assert_eq!(generator.generate().worker(), WORKER);
+
+    assert!(matches!(
+      SnowflakeGenerator::with_worker_provider(|| Ok::<_, SnowflakeError>(WORKER_MAX + 1), PROCESS, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange { field: "worker", .. })
+    ));
+    assert!(matches!(
+      SnowflakeGenerator::with_worker_provider(|| Err("lease unavailable"), PROCESS, AIRDASH_EPOCH),
+      Err(SnowflakeError::WorkerProvider(_))
+    ));
+  }
+
+  #[test]
+  fn test_collides_with() {
+    let generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    assert!(generator.collides_with(&SnowflakeGenerator::new(WORKER, PROCESS)));
+    assert!(!generator.collides_with(&SnowflakeGenerator::new(WORKER, PROCESS + 1)));
+    assert!(!generator.collides_with(&SnowflakeGenerator::new(WORKER + 1, PROCESS)));
+  }
+
+  #[test]
+  fn test_with_high_water_mark() {
+    // the clock went back a minute since the previous instance
+    let clock = ManualClock::new(AIRDASH_EPOCH + 1_000);
+    let high_water_mark = AIRDASH_EPOCH + 61_000;
+    let last = Snowflake::from_unix_millis(WORKER, PROCESS, INCREMENT_MAX, high_water_mark, AIRDASH_EPOCH);
+
+    let mut generator =
+      SnowflakeGenerator::with_high_water_mark(WORKER, PROCESS, AIRDASH_EPOCH, high_water_mark).with_clock(&clock);
+
+    let started = Instant::now();
+    let snowflakes: Vec<_> = (0..=INCREMENT_MAX as usize + 1).map(|_| generator.generate()).collect();
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(snowflakes[0].offset_timestamp(), high_water_mark + 1);
+    assert!(snowflakes[0] > last);
+    assert!(snowflakes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(generator.high_water_mark(), high_water_mark + 2);
+
+    // once the clock catches up, it's used again
+    clock.set(high_water_mark + 10);
+    assert_eq!(generator.generate().offset_timestamp(), high_water_mark + 10);
+  }
+
+  #[test]
+  fn test_high_water_mark_advances() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock);
+
+    generator.generate();
+    assert_eq!(generator.high_water_mark(), AIRDASH_EPOCH + 1_000);
+
+    clock.0.set(AIRDASH_EPOCH + 2_000);
+    generator.generate();
+    assert_eq!(generator.high_water_mark(), AIRDASH_EPOCH + 2_000);
+  }
+
+  #[test]
+  fn test_enumerated() {
+    let items = SnowflakeGenerator::new(WORKER, PROCESS)
+      .enumerated()
+      .take(5)
+      .collect::<Vec<_>>();
+
+    let indices = items.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+    let unique = items.iter().map(|(_, snowflake)| *snowflake).collect::<HashSet<_>>();
+
+    assert_eq!(indices, (0..5).collect::<Vec<_>>());
+    assert_eq!(unique.len(), 5);
+  }
+
+  #[test]
+  fn test_could_have_produced() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    let snowflake = generator.generate();
+
+    assert!(generator.could_have_produced(&snowflake));
+    assert!(!generator.could_have_produced(&snowflake.with_worker(WORKER + 1)));
+    assert!(!generator.could_have_produced(&snowflake.with_process(PROCESS + 1)));
+    assert!(!generator.could_have_produced(&snowflake.with_timestamp(snowflake.timestamp() + 3_600_000)));
+  }
+
+  #[test]
+  fn test_could_have_produced_custom_epoch() {
+    // a million seconds before AIRDASH_EPOCH, so its snowflakes decode as being
+    // that far in the future with AIRDASH_EPOCH
+    let mut generator = SnowflakeGenerator::new_with_epoch(WORKER, PROCESS, AIRDASH_EPOCH - 1_000_000_000);
+
+    let parsed: Snowflake = generator.generate().to_string().parse().unwrap();
+
+    assert_eq!(parsed.epoch(), AIRDASH_EPOCH);
+    assert!(generator.could_have_produced(&parsed));
+    assert!(generator.could_have_produced(&Snowflake::from_value(parsed.value())));
+    assert!(!SnowflakeGenerator::new(WORKER, PROCESS).could_have_produced(&parsed));
+  }
+
+  #[test]
+  fn test_display() {
+    let generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    assert_eq!(
+      generator.to_string(),
+      "SnowflakeGenerator(worker=8, process=26, epoch=1420070400000)"
+    );
+  }
+
+  #[test]
+  fn test_with_wall_clock() {
+    let items = SnowflakeGenerator::new(WORKER, PROCESS)
+      .with_wall_clock()
+      .take(10)
+      .collect::<Vec<_>>();
+
+    for pair in items.windows(2) {
+      assert!(pair[0].0 < pair[1].0);
+      assert!(pair[0].1 <= pair[1].1);
+    }
+  }
+
+  #[test]
+  fn test_builder_increment_floor() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .increment_floor(100)
+      .build()
+      .unwrap()
+      .with_clock(&clock);
+
+    assert_eq!(generator.generate().increment(), 100);
+    assert_eq!(generator.generate().increment(), 101);
+
+    clock.0.set(AIRDASH_EPOCH + 1_001);
+    assert_eq!(generator.generate().increment(), 100);
+
+    assert!(matches!(
+      SnowflakeGenerator::builder().increment_floor(INCREMENT_MAX + 1).build(),
+      Err(SnowflakeError::FieldOutOfRange {
+        field: "increment floor",
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn test_exhaustion_spin_wait() {
+    let clock = ThawingClock {
+      millis: AIRDASH_EPOCH + 1_000,
+      frozen_reads: Cell::new(INCREMENT_MAX as usize + 1 + 100),
+    };
+    let mut generator = exhausted_generator(ExhaustionPolicy::SpinWait, &clock);
+
+    let snowflake = generator.try_generate().unwrap();
+
+    assert_eq!(snowflake.timestamp(), 1_001);
+    assert_eq!(snowflake.increment(), 0);
+  }
+
+  #[test]
+  fn test_exhaustion_sleep_until_next_ms() {
+    let clock = ThawingClock {
+      millis: AIRDASH_EPOCH + 1_000,
+      frozen_reads: Cell::new(INCREMENT_MAX as usize + 1 + 3),
+    };
+    let mut generator = exhausted_generator(ExhaustionPolicy::SleepUntilNextMs, &clock);
+
+    let snowflake = generator.try_generate().unwrap();
+
+    assert_eq!(snowflake.timestamp(), 1_001);
+    assert_eq!(snowflake.increment(), 0);
+  }
+
+  #[test]
+  fn test_sleep_respects_max_spin() {
+    let clock = ManualClock::new(AIRDASH_EPOCH + 60_000);
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .exhaustion_policy(ExhaustionPolicy::SleepUntilNextMs)
+      .max_spin(Duration::from_millis(20))
+      .build()
+      .unwrap()
+      .with_clock(&clock);
+
+    for _ in 0..=INCREMENT_MAX {
+      generator.try_generate().unwrap();
+    }
+
+    // a clock that jumped back a minute must not put the generator to sleep for a
+    // minute
+    clock.set(AIRDASH_EPOCH);
+    let started = Instant::now();
+
+    assert!(matches!(generator.try_generate(), Err(SnowflakeError::ClockStalled)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+  }
+
+  #[test]
+  fn test_exhaustion_error() {
+    let clock = ThawingClock {
+      millis: AIRDASH_EPOCH + 1_000,
+      frozen_reads: Cell::new(usize::MAX),
+    };
+    let mut generator = exhausted_generator(ExhaustionPolicy::Error, &clock);
+
+    assert!(matches!(
+      generator.try_generate(),
+      Err(SnowflakeError::SequenceExhausted)
+    ));
+  }
+
+  #[test]
+  fn test_max_spin_clock_stalled() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .max_spin(Duration::from_millis(20))
+      .build()
+      .unwrap()
+      .with_clock(&clock);
+
+    for _ in 0..=INCREMENT_MAX {
+      generator.try_generate().unwrap();
+    }
+
+    let started = Instant::now();
+
+    assert!(matches!(generator.try_generate(), Err(SnowflakeError::ClockStalled)));
+    assert!(started.elapsed() >= Duration::from_millis(20));
+
+    clock.0.set(AIRDASH_EPOCH + 1_001);
+    assert_eq!(generator.try_generate().unwrap().increment(), 0);
+  }
+
+  #[test]
+  fn test_size_hint() {
+    let generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    assert_eq!(generator.size_hint(), (usize::MAX, None));
+    assert_eq!(generator.take(3).size_hint(), (3, Some(3)));
+  }
+
+  #[test]
+  fn test_monotonic() {
+    let mut generator = SnowflakeGenerator::monotonic(WORKER, PROCESS, AIRDASH_EPOCH + 1_000);
+
+    let snowflakes: Vec<_> = (0..=INCREMENT_MAX).map(|_| generator.generate()).collect();
+
+    assert!(snowflakes.iter().all(|snowflake| snowflake.timestamp() == 1_000));
+    assert!(snowflakes
+      .iter()
+      .enumerate()
+      .all(|(increment, snowflake)| snowflake.increment() == increment as u16));
+
+    let peeked = generator.peek();
+    let next = generator.generate();
+
+    assert_eq!((next.timestamp(), next.increment()), (1_001, 0));
+    assert_eq!(peeked, next);
+    assert!(snowflakes.last().unwrap() < &next);
+  }
+
+  #[test]
+  fn test_spawn_producer() {
+    let (sender, receiver) = std::sync::mpsc::sync_channel(16);
+    let producer = SnowflakeGenerator::new(WORKER, PROCESS).spawn_producer(sender);
+
+    let snowflakes: HashSet<_> = receiver.iter().take(1_000).collect();
+    drop(receiver);
+
+    producer.join().unwrap();
+    assert_eq!(snowflakes.len(), 1_000);
+  }
+
+  #[test]
+  fn test_self_test() {
+    let result = SnowflakeGenerator::new(WORKER, PROCESS).self_test(Duration::from_millis(50));
+
+    assert_eq!(result.duplicates, 0);
+    assert!(result.generated > 0);
+    assert!(result.rate_per_sec > 0.0);
+  }
+
+  #[test]
+  fn test_generate_into() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+    let mut buf = Vec::with_capacity(1_000);
+    let mut seen = HashSet::new();
+
+    for _ in 0..2 {
+      generator.generate_into(&mut buf, 1_000);
+
+      assert_eq!(buf.len(), 1_000);
+      assert_eq!(buf.capacity(), 1_000);
+      seen.extend(buf.iter().copied());
+    }
+
+    assert_eq!(seen.len(), 2_000);
+  }
+
+  #[test]
+  fn test_generate_at() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+    let timestamp = datetime!(2020-02-02 20:20:20.020 UTC);
+
+    let snowflakes: Vec<_> = (0..3).map(|_| generator.generate_at(timestamp).unwrap()).collect();
+
+    assert!(snowflakes.iter().all(|snowflake| snowflake.to_datetime() == timestamp));
+    assert_eq!(
+      snowflakes
+        .iter()
+        .map(|snowflake| snowflake.increment())
+        .collect::<Vec<_>>(),
+      [0, 1, 2]
+    );
+    assert_eq!(generator.generate().increment(), 0);
+
+    for _ in 3..=INCREMENT_MAX {
+      generator.generate_at(timestamp).unwrap();
+    }
+
+    assert!(matches!(
+      generator.generate_at(timestamp),
+      Err(SnowflakeError::SequenceExhausted)
+    ));
+    assert_eq!(
+      generator
+        .generate_at(timestamp + time::Duration::MILLISECOND)
+        .unwrap()
+        .increment(),
+      0
+    );
+    // going back to a millisecond resumes its increments
+    assert!(matches!(
+      generator.generate_at(timestamp),
+      Err(SnowflakeError::SequenceExhausted)
+    ));
+
+    assert!(matches!(
+      generator.generate_at(datetime!(2014-01-01 0:00 UTC)),
+      Err(SnowflakeError::TimestampBeforeEpoch)
+    ));
+  }
+
+  #[test]
+  fn test_generate_at_out_of_order() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+    let first = datetime!(2020-02-02 20:20:20.020 UTC);
+    let second = first - time::Duration::SECOND;
+
+    let increments = [first, second, first, second, first]
+      .map(|timestamp| generator.generate_at(timestamp).unwrap())
+      .map(|snowflake| (snowflake.to_datetime(), snowflake.increment()));
+
+    assert_eq!(
+      increments,
+      [(first, 0), (second, 0), (first, 1), (second, 1), (first, 2)]
+    );
+
+    for offset in 1..=SnowflakeGenerator::<SystemClock>::BACKFILL_CAPACITY as i64 {
+      generator
+        .generate_at(first + time::Duration::milliseconds(offset))
+        .unwrap();
+    }
+
+    assert!(matches!(
+      generator.generate_at(second),
+      Err(SnowflakeError::BackfillEvicted)
+    ));
+    assert!(matches!(
+      generator.generate_at(first),
+      Err(SnowflakeError::BackfillEvicted)
+    ));
+    assert_eq!(
+      generator
+        .generate_at(first + time::Duration::MILLISECOND)
+        .unwrap()
+        .increment(),
+      1
+    );
+  }
+
+  #[test]
+  fn test_for_current_thread() {
+    let process = || {
+      thread::spawn(|| {
+        let generator = SnowflakeGenerator::for_current_thread(WORKER, AIRDASH_EPOCH)?;
+        let again = SnowflakeGenerator::for_current_thread(WORKER, AIRDASH_EPOCH)?;
+
+        assert!(generator.collides_with(&again));
+        Ok::<_, SnowflakeError>(generator.process)
+      })
+      .join()
+      .unwrap()
+    };
+
+    let processes: Vec<_> = (0..4).map(|_| process().unwrap()).collect();
+    assert_eq!(processes.iter().collect::<HashSet<_>>().len(), 4);
+
+    // the only test allocating processes, so it may use up the rest
+    let results: Vec<_> = (4..=PROCESS_MAX).map(|_| process()).collect();
+    assert!(results.iter().all(Result::is_ok));
+    assert!(matches!(
+      process(),
+      Err(SnowflakeError::FieldOutOfRange { field: "process", .. })
+    ));
+  }
+
+  #[test]
+  fn test_reserve_increment_zero() {
+    let clock = ManualClock::new(AIRDASH_EPOCH + 1_000);
+    let mut generator = SnowflakeGenerator::builder()
+      .worker(WORKER)
+      .process(PROCESS)
+      .reserve_increment_zero(true)
+      .build()
+      .unwrap()
+      .with_clock(&clock);
+
+    let header = generator.generate_header();
+    assert_eq!((header.timestamp(), header.increment()), (1_000, 0));
+
+    for _ in 0..10 {
+      assert_ne!(generator.generate().increment(), 0);
+    }
+
+    clock.advance(1);
+    assert_eq!(generator.generate().increment(), 1);
+    assert_eq!(generator.generate_header().increment(), 0);
+
+    // the second header of a millisecond waits for the next one
+    let thread_clock = &clock;
+    thread::scope(|scope| {
+      scope.spawn(move || {
+        sleep(Duration::from_millis(10));
+        thread_clock.advance(1);
+      });
+
+      let next = generator.generate_header();
+      assert_eq!((next.timestamp(), next.increment()), (1_002, 0));
+    });
+  }
+
+  #[test]
+  fn test_generate_header_without_reserve() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    assert!(matches!(
+      generator.try_generate_header(),
+      Err(SnowflakeError::IncrementZeroNotReserved)
+    ));
+  }
+
+  #[test]
+  fn test_peek() {
+    let clock = ManualClock::new(AIRDASH_EPOCH + 1_000);
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock);
+
+    for _ in 0..3 {
+      let peeked = generator.peek();
+
+      assert_eq!(generator.peek(), peeked);
+      assert_eq!(generator.generate(), peeked);
+    }
+
+    clock.advance(1);
+    let peeked = generator.peek();
+    assert_eq!((peeked.timestamp(), peeked.increment()), (1_001, 0));
+    assert_eq!(generator.generate(), peeked);
+
+    for _ in 1..=INCREMENT_MAX {
+      generator.generate();
+    }
+
+    let peeked = generator.peek();
+    assert_eq!((peeked.timestamp(), peeked.increment()), (1_002, 0));
+    clock.advance(1);
+    assert_eq!(generator.generate(), peeked);
+  }
+
+  #[test]
+  fn test_peek_system_clock() {
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS);
+
+    let peeked = generator.peek();
+    let generated = generator.generate();
+
+    // unless the millisecond rolled over in between
+    assert!(generated == peeked || generated.timestamp() > peeked.timestamp());
+  }
+
+  #[test]
+  fn test_strictly_increasing_within_millisecond() {
+    let clock = TestClock(Cell::new(AIRDASH_EPOCH + 1_000));
+    let mut generator = SnowflakeGenerator::new(WORKER, PROCESS).with_clock(&clock);
+
+    let snowflakes: Vec<_> = (0..=INCREMENT_MAX).map(|_| generator.generate()).collect();
+
+    assert!(snowflakes.iter().all(|snowflake| snowflake.timestamp() == 1_000));
+    assert!(snowflakes.windows(2).all(|pair| pair[1].value() > pair[0].value()));
+  }
 }
@@ -1,16 +1,95 @@
+mod analysis;
+#[cfg(feature = "async")]
+mod async_generator;
+#[cfg(feature = "bytemuck")]
+mod bytemuck;
+pub mod checked;
+mod clock;
+#[cfg(feature = "serde")]
+pub mod codecs;
+mod context;
+mod custom;
+pub mod epochs;
+mod error;
+mod ext;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod generator;
+mod oldest_first;
+mod recording;
+mod round_robin;
 #[cfg(feature = "serde")]
-mod serde;
+pub mod serde;
 mod snowflake;
 #[cfg(feature = "ts_rs")]
 mod ts_rs;
 
 use time::OffsetDateTime;
 
-pub use crate::generator::SnowflakeGenerator;
+pub use crate::analysis::{approximate_rate, decode_columns, find_sequence_gaps, newer_than, DecodedColumns};
+#[cfg(feature = "async")]
+pub use crate::async_generator::AsyncSnowflakeGenerator;
+pub use crate::clock::{ClockSource, ManualClock, MonotonicWallClock, SystemClock};
+pub use crate::context::{DecodedSnowflake, SnowflakeContext};
+pub use crate::custom::CustomSnowflake;
+pub use crate::error::{SnowflakeError, UrlParseError};
+pub use crate::ext::SnowflakeExt;
+pub use crate::generator::{ExhaustionPolicy, SelfTestResult, SnowflakeGenerator, SnowflakeGeneratorBuilder};
+pub use crate::oldest_first::OldestFirst;
+pub use crate::recording::RecordingGenerator;
+pub use crate::round_robin::{generate_round, RoundRobinGenerator};
+#[cfg(feature = "serde")]
+pub use crate::serde::NumericSnowflake;
 pub use crate::snowflake::Snowflake;
+#[cfg(feature = "ts_rs")]
+pub use crate::ts_rs::TsBinding;
 
 pub const AIRDASH_EPOCH: u64 = 1420070400000;
 
+pub const TIMESTAMP_BITS: u32 = 42;
+pub const WORKER_BITS: u32 = 5;
+pub const PROCESS_BITS: u32 = 5;
+pub const INCREMENT_BITS: u32 = 12;
+
+pub const TIMESTAMP_MAX: u64 = (1 << TIMESTAMP_BITS) - 1;
+pub const WORKER_MAX: u8 = (1 << WORKER_BITS) - 1;
+pub const PROCESS_MAX: u8 = (1 << PROCESS_BITS) - 1;
+pub const INCREMENT_MAX: u16 = (1 << INCREMENT_BITS) - 1;
+
+/// Largest offset timestamp the checked constructors and
+/// [`Snowflake::checked_from_value`] accept. It leaves bit 63 clear, so the
+/// value survives signed 64-bit storage.
+pub const SIGNED_TIMESTAMP_MAX: u64 = TIMESTAMP_MAX >> 1;
+
+/// Digits of the longest snowflake in decimal, [`u64::MAX`].
+pub const SNOWFLAKE_MAX_DECIMAL_WIDTH: usize = 20;
+
 #[inline]
 pub(crate) const fn millis(datetime: OffsetDateTime) -> u64 { (datetime.unix_timestamp_nanos() / 1_000_000) as u64 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const _: () = assert!(TIMESTAMP_BITS + WORKER_BITS + PROCESS_BITS + INCREMENT_BITS == u64::BITS);
+  const _: () = assert!(SNOWFLAKE_MAX_DECIMAL_WIDTH == u64::MAX.ilog10() as usize + 1);
+
+  #[test]
+  fn test_max_values() {
+    assert_eq!(TIMESTAMP_MAX, 4398046511103);
+    assert_eq!(WORKER_MAX, 31);
+    assert_eq!(PROCESS_MAX, 31);
+    assert_eq!(INCREMENT_MAX, 4095);
+    assert_eq!(Snowflake::from_value(i64::MAX as u64).timestamp(), SIGNED_TIMESTAMP_MAX);
+  }
+
+  #[test]
+  fn test_max_values_fit_fields() {
+    let snowflake = Snowflake::from_value(u64::MAX);
+
+    assert_eq!(snowflake.timestamp(), TIMESTAMP_MAX);
+    assert_eq!(snowflake.worker(), WORKER_MAX);
+    assert_eq!(snowflake.process(), PROCESS_MAX);
+    assert_eq!(snowflake.increment(), INCREMENT_MAX);
+  }
+}
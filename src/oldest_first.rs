@@ -0,0 +1,45 @@
+use std::cmp::Ordering;
+
+use crate::Snowflake;
+
+/// Orders snowflakes in reverse, so a
+/// [`BinaryHeap`](std::collections::BinaryHeap) pops the oldest one first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OldestFirst(pub Snowflake);
+
+impl PartialOrd for OldestFirst {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for OldestFirst {
+  fn cmp(&self, other: &Self) -> Ordering { other.0.cmp(&self.0) }
+}
+
+impl From<Snowflake> for OldestFirst {
+  fn from(snowflake: Snowflake) -> Self { Self(snowflake) }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BinaryHeap;
+
+  use super::*;
+  use crate::SnowflakeGenerator;
+
+  #[test]
+  fn test_pops_oldest_first() {
+    let snowflakes = SnowflakeGenerator::new(8, 26).take(5).collect::<Vec<_>>();
+
+    let mut heap = BinaryHeap::new();
+
+    for snowflake in snowflakes.iter().rev() {
+      heap.push(OldestFirst(*snowflake));
+    }
+
+    let popped = std::iter::from_fn(|| heap.pop())
+      .map(|oldest| oldest.0)
+      .collect::<Vec<_>>();
+
+    assert_eq!(popped, snowflakes);
+  }
+}
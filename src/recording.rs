@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+use crate::{ClockSource, Snowflake, SnowflakeGenerator, SystemClock};
+
+/// Remembers the last `capacity` snowflakes a generator produced, e.g. to
+/// check reports of duplicates against them.
+#[derive(Debug)]
+pub struct RecordingGenerator<C = SystemClock> {
+  generator: SnowflakeGenerator<C>,
+  capacity: usize,
+  recent: VecDeque<Snowflake>,
+}
+
+impl<C: ClockSource> RecordingGenerator<C> {
+  pub fn new(generator: SnowflakeGenerator<C>, capacity: usize) -> Self {
+    Self {
+      generator,
+      capacity,
+      recent: VecDeque::with_capacity(capacity),
+    }
+  }
+
+  pub fn generate(&mut self) -> Snowflake {
+    let snowflake = self.generator.generate();
+
+    if self.capacity > 0 {
+      if self.recent.len() == self.capacity {
+        self.recent.pop_front();
+      }
+
+      self.recent.push_back(snowflake);
+    }
+
+    snowflake
+  }
+
+  /// The recorded snowflakes, oldest first.
+  pub fn recent(&self) -> &VecDeque<Snowflake> { &self.recent }
+}
+
+impl<C: ClockSource> Iterator for RecordingGenerator<C> {
+  type Item = Snowflake;
+
+  fn next(&mut self) -> Option<Self::Item> { Some(self.generate()) }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_keeps_most_recent() {
+    let mut generator = RecordingGenerator::new(SnowflakeGenerator::new(8, 26), 3);
+
+    let snowflakes: Vec<_> = (0..5).map(|_| generator.generate()).collect();
+
+    assert_eq!(*generator.recent(), &snowflakes[2..]);
+  }
+
+  #[test]
+  fn test_zero_capacity() {
+    let mut generator = RecordingGenerator::new(SnowflakeGenerator::new(8, 26), 0);
+
+    generator.generate();
+
+    assert!(generator.recent().is_empty());
+  }
+}
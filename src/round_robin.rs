@@ -0,0 +1,136 @@
+use crate::{ClockSource, Snowflake, SnowflakeError, SnowflakeGenerator};
+
+/// Spreads one stream of snowflakes over several processes of a worker, which
+/// allows `processes.len() * 4096` snowflakes per millisecond.
+#[derive(Debug)]
+pub struct RoundRobinGenerator {
+  generators: Vec<SnowflakeGenerator>,
+  next: usize,
+}
+
+impl RoundRobinGenerator {
+  /// Fails if the worker or a process is out of range, or if `processes` is
+  /// empty or contains duplicates, which would generate colliding snowflakes.
+  pub fn new(worker: u8, processes: &[u8], epoch: u64) -> Result<Self, SnowflakeError> {
+    if processes.is_empty() {
+      return Err(SnowflakeError::NoProcesses);
+    }
+
+    for (index, &process) in processes.iter().enumerate() {
+      if processes[..index].contains(&process) {
+        return Err(SnowflakeError::DuplicateProcess(process));
+      }
+    }
+
+    Ok(Self {
+      generators: processes
+        .iter()
+        .map(|&process| {
+          SnowflakeGenerator::builder()
+            .worker(worker)
+            .process(process)
+            .epoch(epoch)
+            .build()
+        })
+        .collect::<Result<_, _>>()?,
+      next: 0,
+    })
+  }
+
+  pub fn generate(&mut self) -> Snowflake {
+    let snowflake = self.generators[self.next].generate();
+
+    self.next = (self.next + 1) % self.generators.len();
+
+    snowflake
+  }
+}
+
+impl Iterator for RoundRobinGenerator {
+  type Item = Snowflake;
+
+  fn next(&mut self) -> Option<Self::Item> { Some(self.generate()) }
+}
+
+/// Generates one snowflake with each of `generators`. They're unique as long
+/// as no two generators [collide](SnowflakeGenerator::collides_with).
+pub fn generate_round<C: ClockSource>(generators: &mut [SnowflakeGenerator<C>]) -> Vec<Snowflake> {
+  debug_assert!(
+    generators
+      .iter()
+      .enumerate()
+      .all(|(index, generator)| !generators[..index].iter().any(|other| other.collides_with(generator))),
+    "generators must not collide"
+  );
+
+  generators.iter_mut().map(SnowflakeGenerator::generate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use super::*;
+  use crate::{AIRDASH_EPOCH, WORKER_MAX};
+
+  const WORKER: u8 = 8;
+  const PROCESSES: [u8; 3] = [3, 7, 26];
+
+  #[test]
+  fn test_cycles_processes() {
+    let snowflakes = RoundRobinGenerator::new(WORKER, &PROCESSES, AIRDASH_EPOCH)
+      .unwrap()
+      .take(10_000)
+      .collect::<Vec<_>>();
+
+    for (index, snowflake) in snowflakes.iter().enumerate() {
+      assert_eq!(snowflake.worker(), WORKER);
+      assert_eq!(snowflake.process(), PROCESSES[index % PROCESSES.len()]);
+    }
+
+    assert_eq!(snowflakes.iter().collect::<HashSet<_>>().len(), snowflakes.len());
+  }
+
+  #[test]
+  fn test_duplicate_process() {
+    assert!(matches!(
+      RoundRobinGenerator::new(WORKER, &[7, 3, 7], AIRDASH_EPOCH),
+      Err(SnowflakeError::DuplicateProcess(7))
+    ));
+  }
+
+  #[test]
+  fn test_no_processes() {
+    assert!(matches!(
+      RoundRobinGenerator::new(WORKER, &[], AIRDASH_EPOCH),
+      Err(SnowflakeError::NoProcesses)
+    ));
+  }
+
+  #[test]
+  fn test_out_of_range() {
+    let field = |worker: u8, processes: &[u8]| match RoundRobinGenerator::new(worker, processes, AIRDASH_EPOCH) {
+      Err(SnowflakeError::FieldOutOfRange { field, value, .. }) => (field, value),
+      result => panic!("expected a field out of range, got {result:?}"),
+    };
+
+    assert_eq!(field(WORKER, &[0, 32]), ("process", 32));
+    assert_eq!(field(WORKER_MAX + 1, &[0]), ("worker", 32));
+  }
+
+  #[test]
+  fn test_generate_round() {
+    let mut generators = [1, 2, 3, 4].map(|worker| SnowflakeGenerator::new(worker, 26));
+
+    let snowflakes = generate_round(&mut generators);
+
+    assert_eq!(
+      snowflakes
+        .iter()
+        .map(|snowflake| snowflake.worker())
+        .collect::<Vec<_>>(),
+      [1, 2, 3, 4]
+    );
+    assert_eq!(snowflakes.iter().collect::<HashSet<_>>().len(), 4);
+  }
+}
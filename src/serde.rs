@@ -1,7 +1,58 @@
+//! [`Serialize`] and [`Deserialize`] for [`Snowflake`], as a string.
+
 use serde::{Deserialize, Serialize};
 use serde_aux::field_attributes::deserialize_number_from_string;
+#[cfg(feature = "serde_json")]
+use serde_json::Value;
 
+pub use crate::codecs::{byte_array, decoded};
 use crate::Snowflake;
+#[cfg(feature = "serde_json")]
+use crate::SnowflakeError;
+
+#[cfg(feature = "serde_json")]
+impl Snowflake {
+  pub fn to_json_value(&self) -> Value { Value::String(self.value().to_string()) }
+
+  /// Accepts both the string and the number form.
+  pub fn from_json_value(value: &Value) -> Result<Self, SnowflakeError> {
+    match value {
+      Value::String(value) => value.parse(),
+      value => value.to_string().parse(),
+    }
+  }
+}
+
+/// Serializes as a number instead of a string, deserializes from either.
+///
+/// Values above 2^53 lose precision when parsed as JavaScript numbers, which
+/// is the case for most snowflakes created with [`AIRDASH_EPOCH`].
+///
+/// [`AIRDASH_EPOCH`]: crate::AIRDASH_EPOCH
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NumericSnowflake(pub Snowflake);
+
+impl From<Snowflake> for NumericSnowflake {
+  fn from(snowflake: Snowflake) -> Self { Self(snowflake) }
+}
+
+impl From<NumericSnowflake> for Snowflake {
+  fn from(numeric: NumericSnowflake) -> Self { numeric.0 }
+}
+
+impl Serialize for NumericSnowflake {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where S: serde::Serializer {
+    serializer.serialize_u64(self.0.value())
+  }
+}
+
+impl<'de> Deserialize<'de> for NumericSnowflake {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where D: serde::Deserializer<'de> {
+    Snowflake::deserialize(deserializer).map(Self)
+  }
+}
 
 impl Serialize for Snowflake {
   fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -18,3 +69,117 @@ impl<'de> Deserialize<'de> for Snowflake {
     Ok(Self::from_value(value))
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use serde::{Deserialize, Serialize};
+  #[cfg(feature = "serde_json")]
+  use serde_json::json;
+
+  use super::*;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Flattened {
+    id: Snowflake,
+    #[serde(flatten)]
+    extra: HashMap<String, Snowflake>,
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let snowflake = Snowflake::from_value(175928847299117063);
+
+    let json = serde_json::to_string(&snowflake).unwrap();
+
+    assert_eq!(json, r#""175928847299117063""#);
+    assert_eq!(serde_json::from_str::<Snowflake>(&json).unwrap(), snowflake);
+    assert_eq!(
+      serde_json::from_str::<Snowflake>("175928847299117063").unwrap(),
+      snowflake
+    );
+  }
+
+  #[test]
+  fn test_flatten_round_trip() {
+    let value = Flattened {
+      id: Snowflake::from_value(175928847299117063),
+      extra: HashMap::from([
+        ("guild".to_string(), Snowflake::from_value(41771983444115456)),
+        ("channel".to_string(), Snowflake::from_value(41771983423143937)),
+      ]),
+    };
+
+    let json = serde_json::to_value(&value).unwrap();
+
+    assert_eq!(json["guild"], "41771983444115456");
+    assert_eq!(serde_json::from_value::<Flattened>(json).unwrap(), value);
+
+    let numeric = r#"{ "id": 175928847299117063, "guild": 41771983444115456, "channel": "41771983423143937" }"#;
+
+    assert_eq!(serde_json::from_str::<Flattened>(numeric).unwrap(), value);
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_to_json_value() {
+    let snowflake = Snowflake::from_value(175928847299117063);
+
+    assert_eq!(snowflake.to_json_value(), json!("175928847299117063"));
+    assert_eq!(snowflake.to_json_value(), serde_json::to_value(snowflake).unwrap());
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_from_json_value() {
+    let snowflake = Snowflake::from_value(175928847299117063);
+
+    assert_eq!(
+      Snowflake::from_json_value(&json!("175928847299117063")).unwrap(),
+      snowflake
+    );
+    assert_eq!(
+      Snowflake::from_json_value(&json!(175928847299117063u64)).unwrap(),
+      snowflake
+    );
+
+    assert!(matches!(
+      Snowflake::from_json_value(&json!(-1)),
+      Err(SnowflakeError::Parse(_))
+    ));
+    assert!(matches!(
+      Snowflake::from_json_value(&json!(1.5)),
+      Err(SnowflakeError::Parse(_))
+    ));
+    assert!(matches!(
+      Snowflake::from_json_value(&json!(null)),
+      Err(SnowflakeError::Parse(_))
+    ));
+  }
+
+  #[test]
+  fn test_numeric_snowflake() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Ids {
+      string: Snowflake,
+      numeric: NumericSnowflake,
+    }
+
+    let snowflake = Snowflake::from_value(175928847299117063);
+
+    let ids = Ids {
+      string: snowflake,
+      numeric: NumericSnowflake(snowflake),
+    };
+
+    let json = serde_json::to_string(&ids).unwrap();
+
+    assert_eq!(json, r#"{"string":"175928847299117063","numeric":175928847299117063}"#);
+    assert_eq!(serde_json::from_str::<Ids>(&json).unwrap(), ids);
+    assert_eq!(
+      serde_json::from_str::<NumericSnowflake>(r#""175928847299117063""#).unwrap(),
+      NumericSnowflake(snowflake)
+    );
+  }
+}
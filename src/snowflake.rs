@@ -1,11 +1,22 @@
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::io::BufRead;
+use std::mem::{align_of, size_of};
+use std::num::NonZeroU64;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, SystemTimeError, UNIX_EPOCH};
 
 use proc_bitfield::bitfield;
 #[cfg(feature = "ts_rs")]
 use specta::Type;
+use time::format_description::FormatItem;
+use time::macros::format_description;
 use time::OffsetDateTime;
 
-use crate::{millis, AIRDASH_EPOCH};
+use crate::{
+  millis, SnowflakeError, UrlParseError, AIRDASH_EPOCH, INCREMENT_BITS, INCREMENT_MAX, PROCESS_BITS, PROCESS_MAX,
+  SIGNED_TIMESTAMP_MAX, SNOWFLAKE_MAX_DECIMAL_WIDTH, WORKER_BITS, WORKER_MAX,
+};
 
 bitfield! {
   /// ```md
@@ -21,8 +32,15 @@ bitfield! {
   /// process: 31
   /// increment: 4095
   /// ```
-  #[derive(Clone, Copy, PartialEq, Eq)]
+  ///
+  /// The snowflake is stored with its epoch in a `u128`, the value in the low
+  /// and the epoch in the high 64 bits. It's `repr(transparent)`, so its
+  /// layout matches `u128` for transmutes and `bytemuck` on the Rust side.
+  /// `u128` has no stable C ABI, so C callers should pass the
+  /// [`value`](Self::value) as a `u64` instead, like the `ffi` module does.
+  #[derive(Clone, Copy, PartialEq, Eq, Hash)]
   #[cfg_attr(feature = "ts_rs", derive(Type))]
+  #[repr(transparent)]
   pub struct Snowflake(pub u128) {
     pub increment: u16 @ 0..12,
     pub process: u8 @ 12..17,
@@ -33,7 +51,13 @@ bitfield! {
   }
 }
 
+const _: () = assert!(size_of::<Snowflake>() == size_of::<u128>() && align_of::<Snowflake>() == align_of::<u128>());
+
 impl Snowflake {
+  /// Digits of [`to_sortable_base64`](Self::to_sortable_base64), enough for
+  /// the 64 bits of the value.
+  pub const SORTABLE_BASE64_LEN: usize = 11;
+
   pub fn new(worker: u8, process: u8, increment: u16) -> Self {
     Self::new_with_timestamp_and_epoch(worker, process, increment, OffsetDateTime::now_utc(), AIRDASH_EPOCH)
   }
@@ -53,7 +77,79 @@ impl Snowflake {
     timestamp: OffsetDateTime,
     epoch: u64,
   ) -> Self {
-    let offset_timestamp_ms = millis(timestamp) - epoch;
+    Self::from_unix_millis(worker, process, increment, millis(timestamp), epoch)
+  }
+
+  /// Inclusive lowest and highest snowflake created in the unix millisecond
+  /// `timestamp_ms`, for range scans over that exact millisecond.
+  pub fn timestamp_prefix_range(timestamp_ms: u64, epoch: u64) -> (Self, Self) {
+    (
+      Self::from_unix_millis(0, 0, 0, timestamp_ms, epoch),
+      Self::from_unix_millis(WORKER_MAX, PROCESS_MAX, INCREMENT_MAX, timestamp_ms, epoch),
+    )
+  }
+
+  /// Like [`timestamp_prefix_range`](Self::timestamp_prefix_range), for the
+  /// whole unix second `unix_second`.
+  pub fn second_bounds(unix_second: u64, epoch: u64) -> (Self, Self) {
+    let start_ms = unix_second * 1000;
+
+    (
+      Self::timestamp_prefix_range(start_ms, epoch).0,
+      Self::timestamp_prefix_range(start_ms + 999, epoch).1,
+    )
+  }
+
+  /// Like [`new_with_timestamp_and_epoch`](Self::new_with_timestamp_and_epoch),
+  /// for epochs before 1970. The epoch is stored as its two's complement, see
+  /// [`signed_epoch`](Self::signed_epoch).
+  pub fn new_with_timestamp_signed_epoch(
+    worker: u8,
+    process: u8,
+    increment: u16,
+    timestamp: OffsetDateTime,
+    epoch_ms: i64,
+  ) -> Self {
+    let offset_timestamp_ms = (timestamp.unix_timestamp_nanos() / 1_000_000 - epoch_ms as i128) as u64;
+
+    Self(0)
+      .with_worker(worker)
+      .with_process(process)
+      .with_increment(increment)
+      .with_timestamp(offset_timestamp_ms)
+      .with_epoch(epoch_ms as u64)
+  }
+
+  /// Like [`new_with_timestamp_and_epoch`](Self::new_with_timestamp_and_epoch),
+  /// but errors instead of truncating fields or wrapping the timestamp
+  /// around. The offset timestamp may be up to
+  /// [`SIGNED_TIMESTAMP_MAX`](crate::SIGNED_TIMESTAMP_MAX).
+  pub fn try_new_with_timestamp_and_epoch(
+    worker: u8,
+    process: u8,
+    increment: u16,
+    timestamp: OffsetDateTime,
+    epoch: u64,
+  ) -> Result<Self, SnowflakeError> {
+    check_field("worker", worker.into(), WORKER_MAX.into())?;
+    check_field("process", process.into(), PROCESS_MAX.into())?;
+    check_field("increment", increment.into(), INCREMENT_MAX.into())?;
+
+    let timestamp_ms =
+      u64::try_from(timestamp.unix_timestamp_nanos() / 1_000_000).map_err(|_| SnowflakeError::TimestampBeforeEpoch)?;
+    let offset_timestamp_ms = timestamp_ms
+      .checked_sub(epoch)
+      .ok_or(SnowflakeError::TimestampBeforeEpoch)?;
+
+    if offset_timestamp_ms > SIGNED_TIMESTAMP_MAX {
+      return Err(SnowflakeError::TimestampOverflow);
+    }
+
+    Ok(Self::from_unix_millis(worker, process, increment, timestamp_ms, epoch))
+  }
+
+  pub(crate) fn from_unix_millis(worker: u8, process: u8, increment: u16, timestamp_ms: u64, epoch: u64) -> Self {
+    let offset_timestamp_ms = timestamp_ms - epoch;
 
     Self(0)
       .with_worker(worker)
@@ -63,17 +159,517 @@ impl Snowflake {
       .with_epoch(epoch)
   }
 
+  /// Lowest snowflake created `ms` milliseconds ago, usable as an inclusive
+  /// lower bound for "recent" queries.
+  pub fn at_millis_ago(ms: u64, epoch: u64) -> Self {
+    Self::new_with_timestamp_and_epoch(
+      0,
+      0,
+      0,
+      OffsetDateTime::now_utc() - time::Duration::milliseconds(ms as i64),
+      epoch,
+    )
+  }
+
   pub fn from_value(value: u64) -> Self { Self(value as u128).with_epoch(AIRDASH_EPOCH) }
 
   pub fn from_value_with_epoch(value: u64, epoch: u64) -> Self { Self(value as u128).with_epoch(epoch) }
 
-  pub fn offset_timestamp(&self) -> u64 { self.timestamp() + self.epoch() }
+  /// The value, unless it's 0.
+  pub fn as_nonzero(&self) -> Option<NonZeroU64> { NonZeroU64::new(self.value()) }
+
+  pub fn from_nonzero(value: NonZeroU64) -> Self { Self::from_value(value.get()) }
+
+  /// Inverse of [`split_u32`](Self::split_u32).
+  pub fn from_u32_halves(high: u32, low: u32) -> Self { Self::from_value((high as u64) << 32 | low as u64) }
+
+  /// Inverse of [`to_generic_41_10_12`](Self::to_generic_41_10_12).
+  pub fn from_generic_41_10_12(value: u64) -> Self { Self::from_value(value & i64::MAX as u64) }
+
+  /// Like [`from_value`](Self::from_value), for untrusted input. Rejects
+  /// values with bit 63 set, i.e. with a timestamp beyond
+  /// [`SIGNED_TIMESTAMP_MAX`](crate::SIGNED_TIMESTAMP_MAX), the same bound as
+  /// [`try_new_with_timestamp_and_epoch`](Self::try_new_with_timestamp_and_epoch).
+  pub fn checked_from_value(value: u64) -> Option<Self> {
+    let snowflake = Self::from_value(value);
+
+    (snowflake.timestamp() <= SIGNED_TIMESTAMP_MAX).then_some(snowflake)
+  }
+
+  /// SQL predicate matching the ids of `column` generated by `worker`, e.g.
+  /// `((id >> 17) & 31) = 8`. `column` is inserted verbatim, so it must not
+  /// come from untrusted input.
+  pub fn worker_matches_sql(worker: u8, column: &str) -> String {
+    format!(
+      "(({column} >> {}) & {WORKER_MAX}) = {worker}",
+      INCREMENT_BITS + PROCESS_BITS
+    )
+  }
+
+  /// Like [`worker_matches_sql`](Self::worker_matches_sql), for the process.
+  pub fn process_matches_sql(process: u8, column: &str) -> String {
+    format!("(({column} >> {INCREMENT_BITS}) & {PROCESS_MAX}) = {process}")
+  }
+
+  /// Parses decimal ASCII digits straight from `bytes`, without validating
+  /// them as UTF-8 first. Unlike [`FromStr`], a leading `+` is rejected.
+  pub fn from_ascii_bytes(bytes: &[u8]) -> Result<Self, SnowflakeError> {
+    if bytes.is_empty() {
+      return Err(SnowflakeError::Empty);
+    }
+
+    let mut value: u64 = 0;
+
+    for &byte in bytes {
+      if !byte.is_ascii_digit() {
+        return Err(SnowflakeError::InvalidDigit(byte));
+      }
+
+      value = value
+        .checked_mul(10)
+        .and_then(|value| value.checked_add((byte - b'0') as u64))
+        .ok_or(SnowflakeError::ValueOverflow)?;
+    }
+
+    Ok(Self::from_value(value))
+  }
+
+  /// Parses the guild, channel and message IDs out of a Discord message link
+  /// like `https://discord.com/channels/111/222/333`. The guild is `None` for
+  /// links to direct messages, which use `@me` in its place.
+  pub fn parse_discord_url(url: &str) -> Result<(Option<Self>, Self, Self), UrlParseError> {
+    const HOSTS: [&str; 4] = ["discord.com", "ptb.discord.com", "canary.discord.com", "discordapp.com"];
+
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let path = url
+      .strip_prefix("https://")
+      .or_else(|| url.strip_prefix("http://"))
+      .and_then(|url| url.split_once('/'))
+      .filter(|(host, _)| HOSTS.contains(host))
+      .and_then(|(_, path)| path.strip_prefix("channels/"))
+      .ok_or(UrlParseError::NotAMessageUrl)?;
+
+    let [guild, channel, message] = path
+      .trim_end_matches('/')
+      .split('/')
+      .collect::<Vec<_>>()
+      .try_into()
+      .map_err(|_| UrlParseError::NotAMessageUrl)?;
+
+    let guild = match guild {
+      "@me" => None,
+      guild => Some(guild.parse()?),
+    };
+
+    Ok((guild, channel.parse()?, message.parse()?))
+  }
+
+  /// Like [`FromStr`], but ignores surrounding ASCII whitespace and one pair
+  /// of surrounding double or single quotes, as found in logs and CSVs.
+  pub fn parse_lenient(s: &str) -> Result<Self, SnowflakeError> {
+    let s = s.trim_ascii();
+    let unquoted = ['"', '\'']
+      .iter()
+      .find_map(|&quote| s.strip_prefix(quote)?.strip_suffix(quote));
+
+    unquoted.unwrap_or(s).trim_ascii().parse()
+  }
+
+  /// Parses `s` as a snowflake with `epoch` and describes its fields, one
+  /// per line, for debugging tools.
+  pub fn explain(s: &str, epoch: u64) -> Result<String, SnowflakeError> {
+    let snowflake = Self::from_value_with_epoch(s.parse()?, epoch);
+    let created_at = match snowflake.to_datetime_opt() {
+      Some(_) => snowflake.created_at_iso8601(),
+      None => "out of range".to_owned(),
+    };
+
+    Ok(format!(
+      "snowflake {snowflake}\ncreated at {created_at}\nworker {}\nprocess {}\nincrement {}\nepoch {epoch}",
+      snowflake.worker(),
+      snowflake.process(),
+      snowflake.increment()
+    ))
+  }
+
+  /// Parses one snowflake per line, trimming whitespace and skipping blank
+  /// lines.
+  pub fn parse_many(reader: impl BufRead) -> impl Iterator<Item = Result<Self, SnowflakeError>> {
+    reader.lines().filter_map(|line| match line {
+      Ok(line) if line.trim().is_empty() => None,
+      Ok(line) => Some(line.trim().parse()),
+      Err(err) => Some(Err(err.into())),
+    })
+  }
+
+  pub fn offset_timestamp(&self) -> u64 { self.timestamp().wrapping_add(self.epoch()) }
+
+  /// Panics if the creation time is out of the range [`OffsetDateTime`]
+  /// supports, see [`to_datetime_opt`](Self::to_datetime_opt).
+  pub fn to_datetime(&self) -> OffsetDateTime { self.to_datetime_opt().expect("snowflake timestamp is out of range") }
+
+  pub fn to_datetime_opt(&self) -> Option<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(self.signed_offset_timestamp() as i128 * 1_000_000).ok()
+  }
+
+  /// The instant the millisecond this snowflake was created in ends, e.g. to
+  /// flush buffers bucketed by millisecond. Panics like
+  /// [`to_datetime`](Self::to_datetime).
+  pub fn next_millisecond_boundary(&self) -> OffsetDateTime { self.to_datetime() + time::Duration::MILLISECOND }
+
+  /// Creation time in RFC 3339 with millisecond precision, the way Discord
+  /// displays it, e.g. `2016-04-30T11:18:25.796Z`.
+  pub fn created_at_iso8601(&self) -> String {
+    const FORMAT: &[FormatItem<'_>] =
+      format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+
+    self
+      .to_datetime()
+      .format(FORMAT)
+      .expect("snowflake timestamp can't be formatted")
+  }
+
+  /// Rebuilds the snowflake at `timestamp`, keeping its other fields. Unlike
+  /// the raw [`with_timestamp`](Self::with_timestamp), the timestamp isn't
+  /// relative to the epoch and is checked to fit.
+  pub fn with_datetime(&self, timestamp: OffsetDateTime) -> Result<Self, SnowflakeError> {
+    Self::try_new_with_timestamp_and_epoch(self.worker(), self.process(), self.increment(), timestamp, self.epoch())
+  }
+
+  /// Whether this snowflake was created in a millisecond before the one
+  /// `cutoff` falls in.
+  pub fn created_before_datetime(&self, cutoff: OffsetDateTime) -> bool {
+    (self.signed_offset_timestamp() as i128) < cutoff_millis(cutoff)
+  }
+
+  /// Whether this snowflake was created in a millisecond after the one
+  /// `cutoff` falls in.
+  pub fn created_after_datetime(&self, cutoff: OffsetDateTime) -> bool {
+    self.signed_offset_timestamp() as i128 > cutoff_millis(cutoff)
+  }
+
+  pub fn signed_epoch(&self) -> i64 { self.epoch() as i64 }
+
+  /// Like [`offset_timestamp`](Self::offset_timestamp), but negative for
+  /// snowflakes created before 1970.
+  pub fn signed_offset_timestamp(&self) -> i64 { self.signed_epoch().saturating_add(self.timestamp() as i64) }
+
+  /// Canonical key for the second this snowflake was created in, with the
+  /// sub-second milliseconds and all other fields zeroed.
+  pub fn floor_to_second(&self) -> Self {
+    let timestamp = self
+      .timestamp()
+      .saturating_sub(self.signed_offset_timestamp().rem_euclid(1000) as u64);
+
+    Self(0).with_timestamp(timestamp).with_epoch(self.epoch())
+  }
+
+  /// Converts to the common layout with an unused sign bit, a 41 bit
+  /// timestamp, a 10 bit machine id and a 12 bit sequence. The machine id is
+  /// `worker << 5 | process`, which puts every field at the same bits as in
+  /// this layout, so only the top timestamp bit (set after 2084 with
+  /// [`AIRDASH_EPOCH`]) is lost.
+  pub fn to_generic_41_10_12(&self) -> u64 { self.value() & i64::MAX as u64 }
+
+  /// Splits the value into its high and low 32 bits, in that order.
+  pub fn split_u32(&self) -> (u32, u32) { ((self.value() >> 32) as u32, self.value() as u32) }
+
+  /// CRC-8 (polynomial `0x07`) of the big-endian value, to catch
+  /// transcription errors such as any single flipped bit.
+  pub fn checksum(&self) -> u8 {
+    self.value().to_be_bytes().iter().fold(0, |crc, byte| {
+      (0..8).fold(crc ^ byte, |crc, _| match crc & 0x80 {
+        0 => crc << 1,
+        _ => crc << 1 ^ 0x07,
+      })
+    })
+  }
+
+  pub fn verify_checksum(&self, checksum: u8) -> bool { self.checksum() == checksum }
+
+  /// Whether both snowflakes were generated on the same worker and process.
+  pub fn is_same_node(&self, other: &Snowflake) -> bool {
+    self.worker() == other.worker() && self.process() == other.process()
+  }
+
+  /// Best-effort fix for snowflakes from other producers that let the
+  /// increment run past [`INCREMENT_MAX`], so it carried into the process and
+  /// worker bits. This crate's setters mask the increment, so its own
+  /// snowflakes never need this. The carry never touches the low 12 bits, so
+  /// those are kept as the increment and the node is reset to the expected
+  /// one. Returns `None` if the node bits are below the expected ones, which
+  /// a carry can't cause.
+  ///
+  /// Only reliable while the carry stayed out of the timestamp bits, which
+  /// can't be detected.
+  pub fn repair_overflow(&self, expected_worker: u8, expected_process: u8) -> Option<Snowflake> {
+    if expected_worker > WORKER_MAX || expected_process > PROCESS_MAX {
+      return None;
+    }
+
+    let node = |worker: u8, process: u8| (worker as u64) << PROCESS_BITS | process as u64;
+
+    (node(self.worker(), self.process()) >= node(expected_worker, expected_process))
+      .then(|| self.with_worker(expected_worker).with_process(expected_process))
+  }
+
+  /// Worker and process packed into the low 10 bits, worker first:
+  /// `(worker << 5) | process`.
+  pub fn node_u16(&self) -> u16 { (self.worker() as u16) << PROCESS_BITS | self.process() as u16 }
+
+  /// Inverse of [`node_u16`](Self::node_u16), with all other fields zeroed.
+  /// Bits above the low 10 are ignored.
+  pub fn from_node_u16(node: u16) -> Self {
+    Self::from_value(0)
+      .with_worker((node >> PROCESS_BITS) as u8)
+      .with_process(node as u8)
+  }
+
+  /// Low-cardinality label of the node, e.g. `w8p26`, for metrics.
+  pub fn node_label(&self) -> String { format!("w{}p{}", self.worker(), self.process()) }
+
+  /// Reads the `len` bits of the value starting at bit `start`, counting
+  /// from the least significant bit, e.g. to probe other layouts.
+  ///
+  /// Panics if `start + len` exceeds 64.
+  pub fn extract_bits(&self, start: u32, len: u32) -> u64 {
+    assert!(
+      start.checked_add(len).is_some_and(|end| end <= u64::BITS),
+      "bits {start}..{start}+{len} are out of range"
+    );
+
+    self.value().checked_shr(start).unwrap_or(0) & u64::MAX.checked_shr(u64::BITS - len).unwrap_or(0)
+  }
+
+  /// The value in binary, split into the timestamp, worker, process and
+  /// increment fields like the layout diagram of [`Snowflake`].
+  pub fn to_bits_string(&self) -> String {
+    format!(
+      "{:042b} {:05b} {:05b} {:012b}",
+      self.timestamp(),
+      self.worker(),
+      self.process(),
+      self.increment()
+    )
+  }
+
+  /// Single-line form of the decoded fields for structured logs, e.g.
+  /// `sf{ts=123,w=8,p=26,i=543}`.
+  pub fn debug_compact(&self) -> String {
+    format!(
+      "sf{{ts={},w={},p={},i={}}}",
+      self.timestamp(),
+      self.worker(),
+      self.process(),
+      self.increment()
+    )
+  }
+
+  /// The snowflake with the next raw value, carrying over into the other
+  /// fields.
+  pub fn next(&self) -> Option<Self> { Some(Self::from_value_with_epoch(self.value().checked_add(1)?, self.epoch())) }
+
+  /// The snowflake with the previous raw value, borrowing from the other
+  /// fields.
+  pub fn prev(&self) -> Option<Self> { Some(Self::from_value_with_epoch(self.value().checked_sub(1)?, self.epoch())) }
+
+  /// Solves for the epoch `id` was generated with, given the instant it was
+  /// actually created at.
+  pub fn infer_epoch(id: &Snowflake, known_creation: OffsetDateTime) -> u64 { millis(known_creation) - id.timestamp() }
+
+  /// Decodes the creation time with each of `epoch_candidates` in turn,
+  /// ignoring the stored epoch, and returns the first epoch giving a
+  /// plausible time: not after `now` and not before 2010. Ambiguous
+  /// snowflakes resolve to the first plausible candidate.
+  pub fn decode_timestamp_with(&self, epoch_candidates: &[u64], now: OffsetDateTime) -> Option<(u64, OffsetDateTime)> {
+    const EARLIEST: OffsetDateTime = time::macros::datetime!(2010-01-01 0:00 UTC);
+
+    epoch_candidates.iter().find_map(|&epoch| {
+      let created_at = self.with_epoch(epoch).to_datetime_opt()?;
+
+      (EARLIEST..=now).contains(&created_at).then_some((epoch, created_at))
+    })
+  }
+
+  /// High 2 bits of the process field, as packed by
+  /// [`SnowflakeGenerator::new_with_region`].
+  ///
+  /// [`SnowflakeGenerator::new_with_region`]: crate::SnowflakeGenerator::new_with_region
+  pub fn region(&self) -> u8 { self.process() >> 3 }
+
+  /// Low 3 bits of the process field, as packed by
+  /// [`SnowflakeGenerator::new_with_region`].
+  ///
+  /// [`SnowflakeGenerator::new_with_region`]: crate::SnowflakeGenerator::new_with_region
+  pub fn local_process(&self) -> u8 { self.process() & 0b111 }
+
+  /// Whether the decoded creation time is more than a day in the future. That's
+  /// the fingerprint of a snowflake built with a timestamp before its epoch,
+  /// where the offset wrapped around.
+  pub fn timestamp_looks_corrupt(&self) -> bool {
+    const MAX_CLOCK_SKEW_MS: u64 = 24 * 60 * 60 * 1000;
+
+    self.signed_offset_timestamp() > (millis(OffsetDateTime::now_utc()) + MAX_CLOCK_SKEW_MS) as i64
+  }
+
+  /// Maps the snowflake onto one of `shards` partitions. The value is mixed
+  /// first, so the distribution is uniform even though the low bits only hold
+  /// the increment.
+  ///
+  /// Panics if `shards` is 0.
+  pub fn partition(&self, shards: u32) -> u32 { (self.fast_hash() % shards as u64) as u32 }
+
+  /// How many distinct snowflakes all nodes together can generate in one
+  /// millisecond.
+  pub const fn ids_per_millisecond() -> u32 {
+    (INCREMENT_MAX as u32 + 1) * (WORKER_MAX as u32 + 1) * (PROCESS_MAX as u32 + 1)
+  }
+
+  /// Shard of a guild with this ID by Discord's formula
+  /// `(guild_id >> 22) % num_shards`, i.e. by timestamp.
+  ///
+  /// Panics if `num_shards` is 0.
+  pub fn shard(&self, num_shards: u64) -> u64 {
+    (self.value() >> (WORKER_BITS + PROCESS_BITS + INCREMENT_BITS)) % num_shards
+  }
+
+  /// A well-mixed hash of the value, for sharding or bloom filters. Unlike
+  /// [`Hash`] with `RandomState`, it's stable across runs and versions.
+  pub fn fast_hash(&self) -> u64 { splitmix64(self.value()) }
+
+  /// Encodes the value as [`SORTABLE_BASE64_LEN`](Self::SORTABLE_BASE64_LEN)
+  /// base64 digits, most significant first, using an alphabet in ASCII order.
+  /// Comparing the strings therefore orders them like the values.
+  pub fn to_sortable_base64(&self) -> String {
+    (0..Self::SORTABLE_BASE64_LEN)
+      .rev()
+      .map(|digit| SORTABLE_BASE64[(self.value() >> (digit * 6)) as usize & 0x3F] as char)
+      .collect()
+  }
+
+  /// Inverse of [`to_sortable_base64`](Self::to_sortable_base64). Only
+  /// accepts its exact output, so `s` has to be
+  /// [`SORTABLE_BASE64_LEN`](Self::SORTABLE_BASE64_LEN) digits long.
+  pub fn from_sortable_base64(s: &str) -> Result<Self, SnowflakeError> {
+    if s.len() != Self::SORTABLE_BASE64_LEN {
+      return Err(SnowflakeError::InvalidLength {
+        expected: Self::SORTABLE_BASE64_LEN,
+        found: s.len(),
+      });
+    }
+
+    let mut value: u64 = 0;
+
+    for byte in s.bytes() {
+      let digit = SORTABLE_BASE64
+        .iter()
+        .position(|&c| c == byte)
+        .ok_or(SnowflakeError::InvalidDigit(byte))?;
+
+      // the 11 digits hold 66 bits, so the first one must not use the top two
+      if value > u64::MAX >> 6 {
+        return Err(SnowflakeError::ValueOverflow);
+      }
+
+      value = value << 6 | digit as u64;
+    }
+
+    Ok(Self::from_value(value))
+  }
+
+  /// The value in lowercase base36, `0-9a-z`.
+  pub fn to_base36(&self) -> String {
+    let mut value = self.value();
+    let mut digits = Vec::new();
+
+    loop {
+      digits.push(char::from_digit((value % 36) as u32, 36).unwrap());
+      value /= 36;
+
+      if value == 0 {
+        return digits.iter().rev().collect();
+      }
+    }
+  }
+
+  /// Inverse of [`to_base36`](Self::to_base36), ignoring case.
+  pub fn from_base36(s: &str) -> Result<Self, SnowflakeError> { Ok(Self::from_value(u64::from_str_radix(s, 36)?)) }
+
+  /// The value as decimal, zero-padded to the 20 digits of [`u64::MAX`], so
+  /// comparing the strings orders them like the values.
+  pub fn to_zero_padded(&self) -> String { format!("{:0SNOWFLAKE_MAX_DECIMAL_WIDTH$}", self.value()) }
+
+  /// Digits of the value in decimal, at most [`SNOWFLAKE_MAX_DECIMAL_WIDTH`].
+  pub fn decimal_width(&self) -> usize { self.value().checked_ilog10().map_or(1, |log| log as usize + 1) }
+
+  /// Inverse of [`to_zero_padded`](Self::to_zero_padded). Accepts any number
+  /// of leading zeros, like [`from_ascii_bytes`](Self::from_ascii_bytes).
+  pub fn from_zero_padded(s: &str) -> Result<Self, SnowflakeError> { Self::from_ascii_bytes(s.as_bytes()) }
+
+  /// Time between the creation of `earlier` and this snowflake, negative if
+  /// `earlier` is actually newer.
+  pub fn duration_since(&self, earlier: &Snowflake) -> time::Duration {
+    time::Duration::milliseconds(self.signed_offset_timestamp() - earlier.signed_offset_timestamp())
+  }
+
+  /// Creation time as a [`SystemTime`], e.g. for `std` timers.
+  pub fn system_time(&self) -> SystemTime {
+    let offset = Duration::from_millis(self.signed_offset_timestamp().unsigned_abs());
+
+    if self.signed_offset_timestamp() < 0 {
+      UNIX_EPOCH - offset
+    } else {
+      UNIX_EPOCH + offset
+    }
+  }
+
+  /// Time passed since this snowflake was created, according to
+  /// [`SystemTime::now`].
+  pub fn elapsed(&self) -> Result<Duration, SystemTimeError> { SystemTime::now().duration_since(self.system_time()) }
+}
+
+fn cutoff_millis(cutoff: OffsetDateTime) -> i128 { cutoff.unix_timestamp_nanos().div_euclid(1_000_000) }
+
+pub(crate) fn check_field(field: &'static str, value: u64, max: u64) -> Result<(), SnowflakeError> {
+  if value > max {
+    return Err(SnowflakeError::FieldOutOfRange { field, value, max });
+  }
+
+  Ok(())
+}
+
+const SORTABLE_BASE64: &[u8; 64] = b"-0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz";
+
+const fn splitmix64(value: u64) -> u64 {
+  let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+  z ^ (z >> 31)
 }
 
 impl Display for Snowflake {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.value()) }
 }
 
+impl PartialOrd for Snowflake {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Snowflake {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self
+      .value()
+      .cmp(&other.value())
+      .then_with(|| self.epoch().cmp(&other.epoch()))
+  }
+}
+
+impl FromStr for Snowflake {
+  type Err = SnowflakeError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> { Ok(Self::from_value(s.parse()?)) }
+}
+
 impl std::fmt::Debug for Snowflake {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     if f.alternate() {
@@ -98,16 +694,38 @@ impl From<i64> for Snowflake {
   fn from(value: i64) -> Self { (value as u64).into() }
 }
 
+/// Big-endian, so the byte order sorts like the value.
+impl From<[u8; 8]> for Snowflake {
+  fn from(bytes: [u8; 8]) -> Self { u64::from_be_bytes(bytes).into() }
+}
+
+/// Big-endian, so the byte order sorts like the value.
+impl From<Snowflake> for [u8; 8] {
+  fn from(snowflake: Snowflake) -> Self { snowflake.value().to_be_bytes() }
+}
+
 #[cfg(test)]
 mod tests {
+  use std::collections::hash_map::DefaultHasher;
+  use std::hash::{Hash, Hasher};
+
+  #[cfg(not(target_arch = "wasm32"))]
+  use proptest::prelude::*;
   use time::macros::datetime;
 
   use super::*;
+  use crate::TIMESTAMP_BITS;
 
   const WORKER: u8 = 8;
   const PROCESS: u8 = 26;
   const INCREMENT: u16 = 543;
 
+  #[test]
+  fn test_layout() {
+    assert_eq!(size_of::<Snowflake>(), 16);
+    assert_eq!(align_of::<Snowflake>(), align_of::<u128>());
+  }
+
   #[test]
   fn test_new() {
     let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
@@ -164,25 +782,878 @@ mod tests {
   }
 
   #[test]
-  fn test_from_value() {
+  fn test_at_millis_ago() {
+    let now = Snowflake::new_with_timestamp_and_epoch(0, 0, 0, OffsetDateTime::now_utc(), AIRDASH_EPOCH);
+
+    let snowflake = Snowflake::at_millis_ago(0, AIRDASH_EPOCH);
+
+    assert!(snowflake.timestamp().abs_diff(now.timestamp()) <= 1);
+    assert_eq!(snowflake.value() & ((1 << 22) - 1), 0);
+
+    let earlier = Snowflake::at_millis_ago(60_000, AIRDASH_EPOCH);
+    let later = Snowflake::new_with_timestamp_and_epoch(0, 0, 0, OffsetDateTime::now_utc(), AIRDASH_EPOCH);
+
+    assert!(later.timestamp() - earlier.timestamp() >= 60_000);
+  }
+
+  #[test]
+  fn test_to_datetime() {
+    let timestamp = datetime!(2022-07-08 09:10:11.123 UTC);
+
+    let snowflake = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, timestamp);
+
+    assert_eq!(snowflake.to_datetime(), timestamp);
+  }
+
+  #[test]
+  fn test_to_datetime_opt() {
     let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
 
-    let value = snowflake.value();
+    assert_eq!(snowflake.to_datetime_opt(), Some(snowflake.to_datetime()));
+    assert_eq!(
+      Snowflake::from_value(u64::MAX).to_datetime_opt(),
+      Some(datetime!(2154-05-15 07:35:11.103 UTC))
+    );
+    assert_eq!(
+      Snowflake::from_value_with_epoch(u64::MAX, u64::MAX / 2).to_datetime_opt(),
+      None
+    );
+  }
 
-    let from_value = Snowflake::from_value(value);
+  #[test]
+  fn test_with_datetime() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+    let timestamp = datetime!(2022-07-08 09:10:11.123 UTC);
 
-    assert_eq!(from_value, snowflake);
+    let moved = snowflake.with_datetime(timestamp).unwrap();
+
+    assert_eq!(moved.worker(), WORKER);
+    assert_eq!(moved.process(), PROCESS);
+    assert_eq!(moved.increment(), INCREMENT);
+    assert_eq!(moved.to_datetime(), timestamp);
+
+    assert!(matches!(
+      snowflake.with_datetime(datetime!(2014-07-08 09:10:11 UTC)),
+      Err(SnowflakeError::TimestampBeforeEpoch)
+    ));
+    assert!(matches!(
+      snowflake.with_datetime(datetime!(2200-01-01 00:00:00 UTC)),
+      Err(SnowflakeError::TimestampOverflow)
+    ));
   }
 
   #[test]
-  fn test_from_value_with_epoch() {
-    let epoch_timestamp = datetime!(2014-07-08 09:10:11).assume_utc();
-    let snowflake = Snowflake::new_with_epoch(WORKER, PROCESS, INCREMENT, millis(epoch_timestamp));
+  fn test_created_at_iso8601() {
+    let snowflake = Snowflake::from_value(175928847299117063);
 
-    let value = snowflake.value();
+    assert_eq!(snowflake.created_at_iso8601(), "2016-04-30T11:18:25.796Z");
+  }
 
-    let from_value = Snowflake::from_value_with_epoch(value, millis(epoch_timestamp));
+  #[test]
+  fn test_elapsed() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
 
-    assert_eq!(from_value, snowflake);
+    let elapsed = snowflake.elapsed().unwrap();
+
+    assert!(elapsed < Duration::from_secs(1));
+  }
+
+  #[test]
+  fn test_timestamp_prefix_range() {
+    let timestamp = datetime!(2022-07-08 09:10:11 UTC);
+
+    let (low, high) = Snowflake::timestamp_prefix_range(millis(timestamp), AIRDASH_EPOCH);
+
+    assert_eq!(high.value() - low.value(), (1 << 22) - 1);
+
+    for (worker, process, increment) in [(0, 0, 0), (WORKER, PROCESS, INCREMENT), (31, 31, 4095)] {
+      let snowflake = Snowflake::new_with_timestamp(worker, process, increment, timestamp);
+
+      assert!(low <= snowflake && snowflake <= high);
+    }
+
+    assert!(Snowflake::new_with_timestamp(31, 31, 4095, timestamp - time::Duration::milliseconds(1)) < low);
+    assert!(Snowflake::new_with_timestamp(0, 0, 0, timestamp + time::Duration::milliseconds(1)) > high);
+  }
+
+  #[test]
+  fn test_new_with_timestamp_signed_epoch() {
+    const EPOCH: i64 = -1_000_000;
+
+    let timestamp = datetime!(2022-07-08 09:10:11).assume_utc();
+
+    let snowflake = Snowflake::new_with_timestamp_signed_epoch(WORKER, PROCESS, INCREMENT, timestamp, EPOCH);
+
+    assert_eq!(snowflake.worker(), WORKER);
+    assert_eq!(snowflake.process(), PROCESS);
+    assert_eq!(snowflake.increment(), INCREMENT);
+    assert_eq!(snowflake.timestamp(), millis(timestamp) + 1_000_000);
+    assert_eq!(snowflake.signed_epoch(), EPOCH);
+    assert_eq!(snowflake.signed_offset_timestamp(), millis(timestamp) as i64);
+    assert_eq!(snowflake.offset_timestamp(), millis(timestamp));
+
+    let pre_unix = datetime!(1969-12-31 23:59:00).assume_utc();
+
+    let snowflake = Snowflake::new_with_timestamp_signed_epoch(WORKER, PROCESS, INCREMENT, pre_unix, EPOCH);
+
+    assert_eq!(snowflake.timestamp(), 940_000);
+    assert_eq!(snowflake.signed_offset_timestamp(), -60_000);
+    assert_eq!(snowflake.to_datetime(), pre_unix);
+    assert_eq!(snowflake.system_time(), UNIX_EPOCH - Duration::from_secs(60));
+    assert!(!snowflake.timestamp_looks_corrupt());
+  }
+
+  #[test]
+  fn test_try_new_with_timestamp_and_epoch() {
+    let timestamp = datetime!(2022-07-08 09:10:11).assume_utc();
+
+    let snowflake =
+      Snowflake::try_new_with_timestamp_and_epoch(WORKER, PROCESS, INCREMENT, timestamp, AIRDASH_EPOCH).unwrap();
+
+    assert_eq!(
+      snowflake,
+      Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, timestamp)
+    );
+
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(32, PROCESS, INCREMENT, timestamp, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange {
+        field: "worker",
+        value: 32,
+        max: 31
+      })
+    ));
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(WORKER, 32, INCREMENT, timestamp, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange { field: "process", .. })
+    ));
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(WORKER, PROCESS, 4096, timestamp, AIRDASH_EPOCH),
+      Err(SnowflakeError::FieldOutOfRange { field: "increment", .. })
+    ));
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(
+        WORKER,
+        PROCESS,
+        INCREMENT,
+        datetime!(2014-07-08 09:10:11).assume_utc(),
+        AIRDASH_EPOCH
+      ),
+      Err(SnowflakeError::TimestampBeforeEpoch)
+    ));
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(
+        WORKER,
+        PROCESS,
+        INCREMENT,
+        datetime!(2200-01-01 00:00:00).assume_utc(),
+        AIRDASH_EPOCH
+      ),
+      Err(SnowflakeError::TimestampOverflow)
+    ));
+  }
+
+  #[test]
+  fn test_from_str() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(snowflake.to_string().parse::<Snowflake>().unwrap(), snowflake);
+    assert!(matches!("abc".parse::<Snowflake>(), Err(SnowflakeError::Parse(_))));
+  }
+
+  #[test]
+  fn test_matches_sql() {
+    assert_eq!(Snowflake::worker_matches_sql(WORKER, "id"), "((id >> 17) & 31) = 8");
+    assert_eq!(Snowflake::process_matches_sql(PROCESS, "id"), "((id >> 12) & 31) = 26");
+  }
+
+  #[test]
+  fn test_from_ascii_bytes() {
+    let error = |bytes: &[u8]| Snowflake::from_ascii_bytes(bytes).unwrap_err();
+
+    assert_eq!(
+      Snowflake::from_ascii_bytes(b"175928847299117063").unwrap().value(),
+      175928847299117063
+    );
+    assert_eq!(
+      Snowflake::from_ascii_bytes(b"18446744073709551615").unwrap().value(),
+      u64::MAX
+    );
+
+    assert!(matches!(error(b""), SnowflakeError::Empty));
+    assert!(matches!(
+      error(b"175928847299117063x"),
+      SnowflakeError::InvalidDigit(b'x')
+    ));
+    assert!(matches!(error(b"+1"), SnowflakeError::InvalidDigit(b'+')));
+    assert!(matches!(error(b"\xFF1"), SnowflakeError::InvalidDigit(0xFF)));
+    assert!(matches!(error(b"18446744073709551616"), SnowflakeError::ValueOverflow));
+  }
+
+  #[test]
+  fn test_parse_many() {
+    let input = "175928847299117063\n\n  41771983444115456 \nnot a snowflake\n0\n";
+
+    let results = Snowflake::parse_many(input.as_bytes()).collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap().value(), 175928847299117063);
+    assert_eq!(results[1].as_ref().unwrap().value(), 41771983444115456);
+    assert!(matches!(results[2], Err(SnowflakeError::Parse(_))));
+    assert_eq!(results[3].as_ref().unwrap().value(), 0);
+  }
+
+  #[test]
+  fn test_next_prev() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(snowflake.next().unwrap().value(), snowflake.value() + 1);
+    assert_eq!(snowflake.prev().unwrap().value(), snowflake.value() - 1);
+    assert_eq!(snowflake.next().unwrap().prev().unwrap(), snowflake);
+
+    let last_increment = Snowflake::from_value(4095);
+
+    assert_eq!(last_increment.next().unwrap().increment(), 0);
+    assert_eq!(last_increment.next().unwrap().process(), 1);
+
+    assert_eq!(Snowflake::from_value(u64::MAX).next(), None);
+    assert_eq!(Snowflake::from_value(0).prev(), None);
+    assert_eq!(Snowflake::from_value(0).next().unwrap().value(), 1);
+  }
+
+  #[test]
+  fn test_floor_to_second() {
+    let first = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-07-08 09:10:11.001 UTC));
+    let second = Snowflake::new_with_timestamp(1, 2, 3, datetime!(2022-07-08 09:10:11.999 UTC));
+    let next = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-07-08 09:10:12.000 UTC));
+
+    assert_eq!(first.floor_to_second(), second.floor_to_second());
+    assert_ne!(second.floor_to_second(), next.floor_to_second());
+    assert_eq!(
+      first.floor_to_second(),
+      Snowflake::new_with_timestamp(0, 0, 0, datetime!(2022-07-08 09:10:11 UTC))
+    );
+    assert_eq!(
+      next.floor_to_second(),
+      next.with_increment(0).with_worker(0).with_process(0)
+    );
+
+    let pre_unix = datetime!(1969-12-31 23:59:58.250 UTC);
+    let snowflake = Snowflake::new_with_timestamp_signed_epoch(WORKER, PROCESS, INCREMENT, pre_unix, -1_000_000);
+
+    assert_eq!(
+      snowflake.floor_to_second().to_datetime(),
+      datetime!(1969-12-31 23:59:58 UTC)
+    );
+  }
+
+  #[test]
+  fn test_checksum() {
+    let snowflake = Snowflake::from_value(175928847299117063);
+
+    let checksum = snowflake.checksum();
+
+    assert_eq!(checksum, snowflake.checksum());
+    assert!(snowflake.verify_checksum(checksum));
+    assert_eq!(Snowflake::from_value(0).checksum(), 0);
+    // CRC-8/SMBUS of "12345678"
+    assert_eq!(Snowflake::from_value(u64::from_be_bytes(*b"12345678")).checksum(), 0xC7);
+
+    for bit in 0..64 {
+      let flipped = Snowflake::from_value(snowflake.value() ^ 1 << bit);
+
+      assert!(
+        !flipped.verify_checksum(checksum),
+        "flipping bit {bit} kept the checksum"
+      );
+    }
+  }
+
+  #[test]
+  fn test_is_same_node() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert!(snowflake.is_same_node(&Snowflake::new(WORKER, PROCESS, 0)));
+    assert!(!snowflake.is_same_node(&Snowflake::new(WORKER, PROCESS + 1, INCREMENT)));
+    assert!(!snowflake.is_same_node(&Snowflake::new(WORKER + 1, PROCESS, INCREMENT)));
+  }
+
+  #[test]
+  fn test_node_label() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(snowflake.node_label(), "w8p26");
+    assert_eq!(Snowflake::from_value(0).node_label(), "w0p0");
+  }
+
+  #[test]
+  fn test_debug_compact() {
+    let snowflake = Snowflake(0)
+      .with_timestamp(123)
+      .with_worker(WORKER)
+      .with_process(PROCESS)
+      .with_increment(INCREMENT);
+
+    assert_eq!(snowflake.debug_compact(), "sf{ts=123,w=8,p=26,i=543}");
+  }
+
+  #[test]
+  fn test_ord() {
+    let earlier = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-07-08 09:10:11 UTC));
+    let later = Snowflake::new_with_timestamp(0, 0, 0, datetime!(2022-07-08 09:10:11.001 UTC));
+
+    assert!(earlier < later);
+    assert!(earlier < earlier.with_increment(INCREMENT + 1));
+    assert_eq!(earlier.cmp(&earlier), Ordering::Equal);
+  }
+
+  #[test]
+  fn test_bytes() {
+    let snowflake = Snowflake::from_value(0x0102030405060708);
+
+    let bytes: [u8; 8] = snowflake.into();
+
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(Snowflake::from(bytes), snowflake);
+
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(Snowflake::from(<[u8; 8]>::from(snowflake)), snowflake);
+  }
+
+  #[test]
+  fn test_generic_41_10_12() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    let generic = snowflake.to_generic_41_10_12();
+
+    assert_eq!(generic >> 63, 0);
+    assert_eq!(generic >> 22, snowflake.timestamp());
+    assert_eq!((generic >> 12) & 0x3FF, (WORKER as u64) << 5 | PROCESS as u64);
+    assert_eq!(generic & 0xFFF, INCREMENT as u64);
+
+    let machine = 0b01000_11010;
+    let generic = (123_456_789 << 22) | (machine << 12) | 42;
+
+    let snowflake = Snowflake::from_generic_41_10_12(generic);
+
+    assert_eq!(snowflake.timestamp(), 123_456_789);
+    assert_eq!(snowflake.worker(), WORKER);
+    assert_eq!(snowflake.process(), PROCESS);
+    assert_eq!(snowflake.increment(), 42);
+    assert_eq!(snowflake.to_generic_41_10_12(), generic);
+  }
+
+  #[test]
+  fn test_split_u32() {
+    let snowflake = Snowflake::from_value(0x0102030405060708);
+
+    assert_eq!(snowflake.split_u32(), (0x01020304, 0x05060708));
+    assert_eq!(Snowflake::from_u32_halves(0x01020304, 0x05060708), snowflake);
+
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+    let (high, low) = snowflake.split_u32();
+
+    assert_eq!(Snowflake::from_u32_halves(high, low), snowflake);
+  }
+
+  #[test]
+  fn test_from_value() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    let value = snowflake.value();
+
+    let from_value = Snowflake::from_value(value);
+
+    assert_eq!(from_value, snowflake);
+  }
+
+  #[test]
+  fn test_checked_from_value() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(Snowflake::checked_from_value(snowflake.value()), Some(snowflake));
+    assert_eq!(
+      Snowflake::checked_from_value(i64::MAX as u64),
+      Some(Snowflake::from_value(i64::MAX as u64))
+    );
+    assert_eq!(Snowflake::checked_from_value(1 << 63), None);
+    assert_eq!(Snowflake::checked_from_value(u64::MAX), None);
+
+    // accepts exactly what the checked constructors produce
+    let last = OffsetDateTime::from_unix_timestamp_nanos(SIGNED_TIMESTAMP_MAX as i128 * 1_000_000).unwrap();
+    let latest = Snowflake::try_new_with_timestamp_and_epoch(WORKER_MAX, PROCESS_MAX, INCREMENT_MAX, last, 0).unwrap();
+
+    assert!(Snowflake::checked_from_value(latest.value()).is_some());
+    assert!(matches!(
+      Snowflake::try_new_with_timestamp_and_epoch(0, 0, 0, last + time::Duration::MILLISECOND, 0),
+      Err(SnowflakeError::TimestampOverflow)
+    ));
+  }
+
+  #[test]
+  fn test_from_value_with_epoch() {
+    let epoch_timestamp = datetime!(2014-07-08 09:10:11).assume_utc();
+    let snowflake = Snowflake::new_with_epoch(WORKER, PROCESS, INCREMENT, millis(epoch_timestamp));
+
+    let value = snowflake.value();
+
+    let from_value = Snowflake::from_value_with_epoch(value, millis(epoch_timestamp));
+
+    assert_eq!(from_value, snowflake);
+  }
+
+  #[test]
+  fn test_partition() {
+    const SHARDS: u32 = 16;
+    const COUNT: usize = 100_000;
+
+    let mut counts = [0usize; SHARDS as usize];
+
+    // what a generator produces in 25 busy milliseconds, fixed to keep it
+    // deterministic
+    for timestamp in 0..25 {
+      for increment in 0..4_000 {
+        let snowflake =
+          Snowflake::from_unix_millis(WORKER, PROCESS, increment, AIRDASH_EPOCH + timestamp, AIRDASH_EPOCH);
+        counts[snowflake.partition(SHARDS) as usize] += 1;
+      }
+    }
+
+    let expected = COUNT / SHARDS as usize;
+
+    for count in counts {
+      assert!(
+        count.abs_diff(expected) <= expected / 20,
+        "{count} is not within 5% of {expected}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_infer_epoch() {
+    let timestamp = datetime!(2022-07-08 09:10:11).assume_utc();
+
+    let snowflake = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, timestamp);
+
+    assert_eq!(Snowflake::infer_epoch(&snowflake, timestamp), AIRDASH_EPOCH);
+  }
+
+  #[test]
+  fn test_timestamp_looks_corrupt() {
+    let pre_epoch = datetime!(2014-07-08 09:10:11).assume_utc();
+
+    // what a release build produces for a timestamp before the epoch
+    let corrupt = Snowflake(0)
+      .with_timestamp(millis(pre_epoch).wrapping_sub(AIRDASH_EPOCH))
+      .with_epoch(AIRDASH_EPOCH);
+
+    assert!(corrupt.timestamp_looks_corrupt());
+    assert!(!Snowflake::new(WORKER, PROCESS, INCREMENT).timestamp_looks_corrupt());
+  }
+
+  #[test]
+  fn test_hash_consistent_with_eq() {
+    let hash = |snowflake: Snowflake| {
+      let mut hasher = DefaultHasher::new();
+      snowflake.hash(&mut hasher);
+      hasher.finish()
+    };
+
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(hash(snowflake), hash(Snowflake::from_value(snowflake.value())));
+  }
+
+  #[test]
+  fn test_fast_hash() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(snowflake.fast_hash(), snowflake.fast_hash());
+    assert_eq!(Snowflake::from_value(0).fast_hash(), 0xE220A8397B1DCDAF);
+
+    let mut high_bits = [0usize; 16];
+
+    // what a generator produces in 4 busy milliseconds, fixed to keep it
+    // deterministic
+    for timestamp in 0..4 {
+      for increment in 0..4_000 {
+        let snowflake =
+          Snowflake::from_unix_millis(WORKER, PROCESS, increment, AIRDASH_EPOCH + timestamp, AIRDASH_EPOCH);
+        high_bits[(snowflake.fast_hash() >> 60) as usize] += 1;
+      }
+    }
+
+    for count in high_bits {
+      assert!(count.abs_diff(1_000) <= 100, "{count} is not within 10% of 1000");
+    }
+  }
+
+  #[test]
+  fn test_region() {
+    let snowflake = Snowflake::new(WORKER, 0b10_101, INCREMENT);
+
+    assert_eq!(snowflake.region(), 0b10);
+    assert_eq!(snowflake.local_process(), 0b101);
+  }
+
+  #[test]
+  fn test_sortable_base64() {
+    let error = |s: &str| Snowflake::from_sortable_base64(s).unwrap_err();
+
+    assert_eq!(Snowflake::from_value(0).to_sortable_base64(), "-----------");
+    assert_eq!(Snowflake::from_value(u64::MAX).to_sortable_base64(), "Ezzzzzzzzzz");
+
+    for value in [0, 175928847299117063, u64::MAX] {
+      let snowflake = Snowflake::from_value(value);
+      assert_eq!(
+        Snowflake::from_sortable_base64(&snowflake.to_sortable_base64()).unwrap(),
+        snowflake
+      );
+    }
+
+    assert!(matches!(
+      error(""),
+      SnowflakeError::InvalidLength { expected: 11, found: 0 }
+    ));
+    assert!(matches!(
+      error("-"),
+      SnowflakeError::InvalidLength { expected: 11, found: 1 }
+    ));
+    assert!(matches!(
+      error("0-----------"),
+      SnowflakeError::InvalidLength {
+        expected: 11,
+        found: 12
+      }
+    ));
+    assert!(matches!(error("-----+-----"), SnowflakeError::InvalidDigit(b'+')));
+    assert!(matches!(error("F----------"), SnowflakeError::ValueOverflow));
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  proptest! {
+    #[test]
+    fn test_sortable_base64_preserves_order(a: u64, b: u64) {
+      let (a, b) = (Snowflake::from_value(a), Snowflake::from_value(b));
+
+      prop_assert_eq!(a.cmp(&b), a.to_sortable_base64().cmp(&b.to_sortable_base64()));
+    }
+  }
+
+  #[test]
+  fn test_repair_overflow() {
+    let original = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-07-08 09:10:11 UTC));
+
+    for overflows in [0, 1, 5, 200] {
+      // a producer that adds the increment without masking it carries into the node
+      // bits
+      let corrupted = Snowflake(original.0 + (overflows << INCREMENT_BITS));
+
+      assert_eq!(corrupted.repair_overflow(WORKER, PROCESS), Some(original));
+    }
+
+    assert_eq!(
+      original.with_process(PROCESS - 1).repair_overflow(WORKER, PROCESS),
+      None
+    );
+    assert_eq!(original.with_worker(WORKER - 1).repair_overflow(WORKER, PROCESS), None);
+    assert_eq!(original.repair_overflow(WORKER_MAX + 1, PROCESS), None);
+  }
+
+  #[test]
+  fn test_to_bits_string() {
+    let snowflake = Snowflake::from(175928847299117063u64);
+    let bits = snowflake.to_bits_string();
+    let segments: Vec<_> = bits.split(' ').collect();
+
+    assert_eq!(
+      segments.iter().map(|segment| segment.len()).collect::<Vec<_>>(),
+      [
+        TIMESTAMP_BITS as usize,
+        WORKER_BITS as usize,
+        PROCESS_BITS as usize,
+        INCREMENT_BITS as usize
+      ]
+    );
+    assert_eq!(segments[1..], ["00001", "00000", "000000000111"]);
+    assert_eq!(u64::from_str_radix(&segments.concat(), 2).unwrap(), snowflake.value());
+  }
+
+  #[test]
+  fn test_zero_padded() {
+    assert_eq!(Snowflake::from_value(0).to_zero_padded(), "00000000000000000000");
+    assert_eq!(
+      Snowflake::from(175928847299117063u64).to_zero_padded(),
+      "00175928847299117063"
+    );
+    assert_eq!(Snowflake::from_value(u64::MAX).to_zero_padded(), "18446744073709551615");
+    assert_eq!(
+      Snowflake::from_zero_padded("00175928847299117063").unwrap(),
+      Snowflake::from(175928847299117063u64)
+    );
+    assert!(Snowflake::from_zero_padded("+0175928847299117063").is_err());
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  proptest! {
+    #[test]
+    fn test_zero_padded_round_trip_and_order(a: u64, b: u64) {
+      let (a, b) = (Snowflake::from_value(a), Snowflake::from_value(b));
+
+      prop_assert_eq!(Snowflake::from_zero_padded(&a.to_zero_padded()).unwrap(), a);
+      prop_assert_eq!(a.cmp(&b), a.to_zero_padded().cmp(&b.to_zero_padded()));
+    }
+  }
+
+  #[test]
+  fn test_system_time() {
+    let snowflake = Snowflake::from(175928847299117063u64);
+    let since_unix = snowflake.system_time().duration_since(UNIX_EPOCH).unwrap();
+
+    assert_eq!(since_unix.as_millis(), 1462015105796);
+    assert_eq!(since_unix.as_millis() as u64, snowflake.offset_timestamp());
+  }
+
+  #[test]
+  fn test_shard() {
+    let guild = Snowflake::from(197038439483310086u64);
+
+    assert_eq!(guild.shard(16), 2);
+    assert_eq!(guild.shard(1), 0);
+    assert_eq!(guild.with_increment(INCREMENT_MAX).shard(16), 2);
+  }
+
+  #[test]
+  fn test_created_before_and_after_datetime() {
+    let cutoff = datetime!(2023-01-01 00:00 UTC);
+    let at = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, cutoff);
+    let before = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-12-31 23:59:59.999 UTC));
+    let after = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2023-01-01 00:00:00.001 UTC));
+
+    assert!(before.created_before_datetime(cutoff));
+    assert!(!before.created_after_datetime(cutoff));
+    assert!(!at.created_before_datetime(cutoff));
+    assert!(!at.created_after_datetime(cutoff));
+    assert!(!at.created_before_datetime(datetime!(2023-01-01 00:00:00.000_5 UTC)));
+    assert!(after.created_after_datetime(cutoff));
+    assert!(!after.created_before_datetime(cutoff));
+    assert!(at.created_after_datetime(datetime!(1969-12-31 23:59 UTC)));
+  }
+
+  #[test]
+  fn test_parse_discord_url() {
+    let ids = |guild: Option<u64>, channel: u64, message: u64| {
+      (
+        guild.map(Snowflake::from),
+        Snowflake::from(channel),
+        Snowflake::from(message),
+      )
+    };
+
+    assert_eq!(
+      Snowflake::parse_discord_url("https://discord.com/channels/111/222/333").unwrap(),
+      ids(Some(111), 222, 333)
+    );
+    assert_eq!(
+      Snowflake::parse_discord_url("https://canary.discord.com/channels/111/222/333?foo=bar").unwrap(),
+      ids(Some(111), 222, 333)
+    );
+    assert_eq!(
+      Snowflake::parse_discord_url("https://discord.com/channels/@me/222/333").unwrap(),
+      ids(None, 222, 333)
+    );
+
+    for url in [
+      "https://discord.com/channels/111/222",
+      "https://discord.com/channels/111/222/333/444",
+      "https://example.com/channels/111/222/333",
+      "discord.com/channels/111/222/333",
+      "https://discord.com/invite/abc",
+    ] {
+      assert!(
+        matches!(Snowflake::parse_discord_url(url), Err(UrlParseError::NotAMessageUrl)),
+        "{url}"
+      );
+    }
+
+    assert!(matches!(
+      Snowflake::parse_discord_url("https://discord.com/channels/111/abc/333"),
+      Err(UrlParseError::InvalidId(SnowflakeError::Parse(_)))
+    ));
+  }
+
+  #[test]
+  fn test_ids_per_millisecond() {
+    const IDS: u32 = Snowflake::ids_per_millisecond();
+
+    assert_eq!(IDS, 4096 * 32 * 32);
+  }
+
+  #[test]
+  fn test_decode_timestamp_with() {
+    const OTHER_EPOCH: u64 = 1_600_000_000_000;
+
+    let now = datetime!(2024-01-01 0:00 UTC);
+    let created_at = datetime!(2023-06-01 12:00 UTC);
+    let snowflake = Snowflake::new_with_timestamp_and_epoch(WORKER, PROCESS, INCREMENT, created_at, OTHER_EPOCH);
+
+    // decodes to 1972 with the unix epoch and to 2017 with the airdash epoch
+    assert_eq!(
+      snowflake.decode_timestamp_with(&[0, OTHER_EPOCH, AIRDASH_EPOCH], now),
+      Some((OTHER_EPOCH, created_at))
+    );
+    assert_eq!(
+      snowflake
+        .decode_timestamp_with(&[AIRDASH_EPOCH, OTHER_EPOCH], now)
+        .map(|(epoch, _)| epoch),
+      Some(AIRDASH_EPOCH)
+    );
+    assert_eq!(
+      snowflake.decode_timestamp_with(&[OTHER_EPOCH], created_at - time::Duration::DAY),
+      None
+    );
+    assert_eq!(snowflake.decode_timestamp_with(&[], now), None);
+  }
+
+  #[test]
+  fn test_decimal_width() {
+    assert_eq!(Snowflake::from_value(0).decimal_width(), 1);
+    assert_eq!(Snowflake::from_value(9).decimal_width(), 1);
+    assert_eq!(Snowflake::from_value(10).decimal_width(), 2);
+    assert_eq!(Snowflake::from(175928847299117063u64).decimal_width(), 18);
+    assert_eq!(
+      Snowflake::from_value(u64::MAX).decimal_width(),
+      SNOWFLAKE_MAX_DECIMAL_WIDTH
+    );
+  }
+
+  #[test]
+  fn test_next_millisecond_boundary() {
+    let snowflake = Snowflake::from(175928847299117063u64);
+
+    assert_eq!(
+      snowflake.next_millisecond_boundary(),
+      datetime!(2016-04-30 11:18:25.797 UTC)
+    );
+    assert_eq!(
+      snowflake.next_millisecond_boundary() - snowflake.to_datetime(),
+      time::Duration::MILLISECOND
+    );
+  }
+
+  #[test]
+  fn test_parse_lenient() {
+    let snowflake = Snowflake::from_value(123);
+
+    assert_eq!(Snowflake::parse_lenient("123").unwrap(), snowflake);
+    assert_eq!(Snowflake::parse_lenient("  123 ").unwrap(), snowflake);
+    assert_eq!(Snowflake::parse_lenient("\"123\"").unwrap(), snowflake);
+    assert_eq!(Snowflake::parse_lenient("\t'123'\n").unwrap(), snowflake);
+    assert_eq!(Snowflake::parse_lenient("\" 123 \"").unwrap(), snowflake);
+
+    assert!(Snowflake::parse_lenient("1 23").is_err());
+    assert!(Snowflake::parse_lenient("\"123'").is_err());
+    assert!(Snowflake::parse_lenient("\"\"123\"\"").is_err());
+    assert!(" 123".parse::<Snowflake>().is_err());
+  }
+
+  #[test]
+  fn test_nonzero() {
+    let snowflake = Snowflake::from(175928847299117063u64);
+
+    assert_eq!(Snowflake::from_value(0).as_nonzero(), None);
+    assert_eq!(snowflake.as_nonzero().map(NonZeroU64::get), Some(175928847299117063));
+    assert_eq!(Snowflake::from_nonzero(snowflake.as_nonzero().unwrap()), snowflake);
+  }
+
+  #[test]
+  fn test_explain() {
+    assert_eq!(
+      Snowflake::explain("175928847299117063", AIRDASH_EPOCH).unwrap(),
+      "snowflake 175928847299117063\ncreated at 2016-04-30T11:18:25.796Z\nworker 1\nprocess 0\nincrement 7\nepoch \
+       1420070400000"
+    );
+    assert!(Snowflake::explain("0", u64::MAX / 2)
+      .unwrap()
+      .contains("created at out of range"));
+    assert!(matches!(
+      Snowflake::explain("abc", AIRDASH_EPOCH),
+      Err(SnowflakeError::Parse(_))
+    ));
+  }
+
+  #[test]
+  fn test_extract_bits() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+
+    assert_eq!(snowflake.extract_bits(0, INCREMENT_BITS), snowflake.increment() as u64);
+    assert_eq!(snowflake.extract_bits(12, PROCESS_BITS), snowflake.process() as u64);
+    assert_eq!(snowflake.extract_bits(17, WORKER_BITS), snowflake.worker() as u64);
+    assert_eq!(snowflake.extract_bits(22, TIMESTAMP_BITS), snowflake.timestamp());
+    assert_eq!(snowflake.extract_bits(0, 64), snowflake.value());
+    assert_eq!(snowflake.extract_bits(64, 0), 0);
+  }
+
+  #[test]
+  #[should_panic(expected = "bits 60..60+5 are out of range")]
+  fn test_extract_bits_out_of_range() { Snowflake::from_value(0).extract_bits(60, 5); }
+
+  #[test]
+  fn test_second_bounds() {
+    let snowflake = Snowflake::from(175928847299117063u64);
+    let (lowest, highest) = Snowflake::second_bounds(1462015105, AIRDASH_EPOCH);
+
+    assert!((lowest..=highest).contains(&snowflake));
+    assert_eq!(lowest.to_datetime(), datetime!(2016-04-30 11:18:25 UTC));
+    assert_eq!((lowest.worker(), lowest.process(), lowest.increment()), (0, 0, 0));
+    assert_eq!(highest.to_datetime(), datetime!(2016-04-30 11:18:25.999 UTC));
+    assert_eq!(
+      (highest.worker(), highest.process(), highest.increment()),
+      (WORKER_MAX, PROCESS_MAX, INCREMENT_MAX)
+    );
+    assert!(!(lowest..=highest).contains(&highest.next().unwrap()));
+  }
+
+  #[test]
+  fn test_duration_since() {
+    let earlier = Snowflake::new_with_timestamp(WORKER, PROCESS, INCREMENT, datetime!(2022-07-08 09:10:11 UTC));
+    let later = Snowflake::new_with_timestamp(1, 2, 3, datetime!(2022-07-08 09:10:11.500 UTC));
+
+    assert_eq!(later.duration_since(&earlier), time::Duration::milliseconds(500));
+    assert_eq!(earlier.duration_since(&later), time::Duration::milliseconds(-500));
+    assert_eq!(earlier.duration_since(&earlier), time::Duration::ZERO);
+  }
+
+  #[test]
+  fn test_base36() {
+    assert_eq!(Snowflake::from_value(0).to_base36(), "0");
+    assert_eq!(Snowflake::from_value(35).to_base36(), "z");
+    assert_eq!(Snowflake::from_value(u64::MAX).to_base36(), "3w5e11264sgsf");
+
+    for value in [0, 36, 175928847299117063, u64::MAX] {
+      let snowflake = Snowflake::from_value(value);
+      assert_eq!(Snowflake::from_base36(&snowflake.to_base36()).unwrap(), snowflake);
+    }
+
+    assert_eq!(
+      Snowflake::from_base36("FF").unwrap(),
+      Snowflake::from_base36("ff").unwrap()
+    );
+    assert_eq!(Snowflake::from_base36("fF").unwrap().value(), 15 * 36 + 15);
+    assert!(matches!(Snowflake::from_base36("f-f"), Err(SnowflakeError::Parse(_))));
+    assert!(matches!(Snowflake::from_base36(""), Err(SnowflakeError::Parse(_))));
+  }
+
+  #[test]
+  fn test_node_u16() {
+    let snowflake = Snowflake::new(WORKER, PROCESS, INCREMENT);
+    let node = snowflake.node_u16();
+
+    assert_eq!(node, (WORKER as u16) << 5 | PROCESS as u16);
+    assert!(Snowflake::from_node_u16(node).is_same_node(&snowflake));
+    assert_eq!(Snowflake::from_node_u16(node).node_u16(), node);
+    assert_eq!(Snowflake::from_node_u16(node).value() >> INCREMENT_BITS, node as u64);
+    assert_eq!(Snowflake::from_node_u16(0xFFFF).node_u16(), 0x3FF);
   }
 }
@@ -1,18 +1,81 @@
-use ts_rs::TS;
+//! The generated binding is configured with a [`TsBinding`], installed before
+//! exporting the bindings. The `ts_rs_branded` feature makes the branded type
+//! the default.
+
+use std::sync::RwLock;
+
+use ts_rs::{ExportError, TS};
 
 use crate::Snowflake;
 
-const NAME: &str = "Snowflake";
+const EXPORT_TO: &str = "bindings/Snowflake.ts";
 const TYPE: &str = "string";
 
+static BINDING: RwLock<Option<TsBinding>> = RwLock::new(None);
+
+/// TypeScript binding of [`Snowflake`], applied to its [`TS`] impl with
+/// [`install`](Self::install).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TsBinding {
+  name: String,
+  branded: bool,
+}
+
+impl TsBinding {
+  /// Type name, `Snowflake` by default.
+  pub fn name(mut self, name: impl Into<String>) -> Self {
+    self.name = name.into();
+    self
+  }
+
+  /// Declares a branded string, so plain strings aren't assignable to it,
+  /// instead of an alias for `string`.
+  pub fn branded(mut self, branded: bool) -> Self {
+    self.branded = branded;
+    self
+  }
+
+  /// Makes the [`TS`] impl of [`Snowflake`] use this binding from now on.
+  pub fn install(self) { *BINDING.write().unwrap() = Some(self); }
+
+  /// Installs this binding and exports it to `path` instead of
+  /// `bindings/Snowflake.ts`. Bindings of other types still import it from
+  /// `bindings/Snowflake.ts`.
+  pub fn export_to(self, path: impl AsRef<std::path::Path>) -> Result<(), ExportError> {
+    self.install();
+    Snowflake::export_to(path)
+  }
+
+  fn current() -> Self { BINDING.read().unwrap().clone().unwrap_or_default() }
+
+  fn inline(&self) -> String {
+    if self.branded {
+      format!("{TYPE} & {{ readonly __brand: \"{}\" }}", self.name)
+    } else {
+      TYPE.into()
+    }
+  }
+
+  fn declaration(&self) -> String { format!("type {} = {};", self.name, self.inline()) }
+}
+
+impl Default for TsBinding {
+  fn default() -> Self {
+    Self {
+      name: "Snowflake".into(),
+      branded: cfg!(feature = "ts_rs_branded"),
+    }
+  }
+}
+
 impl TS for Snowflake {
-  const EXPORT_TO: Option<&'static str> = Some("bindings/Snowflake.ts");
+  const EXPORT_TO: Option<&'static str> = Some(EXPORT_TO);
 
-  fn decl() -> String { format!("type {NAME} = {TYPE};") }
+  fn decl() -> String { TsBinding::current().declaration() }
 
-  fn name() -> String { NAME.into() }
+  fn name() -> String { TsBinding::current().name }
 
-  fn inline() -> String { TYPE.into() }
+  fn inline() -> String { TsBinding::current().inline() }
 
   fn dependencies() -> Vec<ts_rs::Dependency>
   where Self: 'static {
@@ -21,3 +84,45 @@ impl TS for Snowflake {
 
   fn transparent() -> bool { false }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_declaration() {
+    let plain = TsBinding::default().branded(false);
+    let branded = plain.clone().name("UserId").branded(true);
+
+    assert_eq!(plain.declaration(), "type Snowflake = string;");
+    assert_eq!(plain.clone().name("UserId").declaration(), "type UserId = string;");
+    assert_eq!(
+      branded.declaration(),
+      r#"type UserId = string & { readonly __brand: "UserId" };"#
+    );
+    assert_eq!(branded.inline(), r#"string & { readonly __brand: "UserId" }"#);
+  }
+
+  // the only test touching the installed binding, so it can't race with others
+  #[test]
+  fn test_install() {
+    assert_eq!(Snowflake::name(), "Snowflake");
+    assert_eq!(Snowflake::decl(), TsBinding::default().declaration());
+
+    TsBinding::default().name("UserId").branded(true).install();
+
+    assert_eq!(Snowflake::name(), "UserId");
+    assert_eq!(
+      Snowflake::decl(),
+      r#"type UserId = string & { readonly __brand: "UserId" };"#
+    );
+    assert_eq!(Snowflake::inline(), r#"string & { readonly __brand: "UserId" }"#);
+
+    TsBinding::default().name("MessageId").branded(false).install();
+
+    assert_eq!(Snowflake::decl(), "type MessageId = string;");
+    assert_eq!(Snowflake::inline(), "string");
+
+    TsBinding::default().install();
+  }
+}